@@ -18,7 +18,7 @@ fn main() {
         
         // Create a syscall to print the character
         statements.push(BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // write syscall
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1), // stdout
                 BFLNode::Variable(var_name), // reference to the character variable
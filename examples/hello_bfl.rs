@@ -21,7 +21,7 @@ fn main() {
         BFLNode::Assign("msg_nl".to_string(), Box::new(BFLNode::Number(10))), // \n
         // Write syscall: write(1, msg, 14)
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // write syscall
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1),                     // fd (stdout)
                 BFLNode::Variable("msg_h".to_string()), // buffer start
@@ -0,0 +1,110 @@
+use ewor_brainfuck::bfl::{BFLCompiler, BFLNode};
+use ewor_brainfuck::bf::{BF, Mode};
+
+fn main() {
+    let mut compiler = BFLCompiler::new();
+
+    let hello_msg = b"Hello from client\n";
+    let connected_msg = b"Connected to server on port 8080\n";
+
+    let program = BFLNode::Block(vec![
+        // Create socket
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("socket".to_string())),
+            vec![
+                BFLNode::Number(2),  // AF_INET
+                BFLNode::Number(1),  // SOCK_STREAM
+                BFLNode::Number(0),  // protocol
+            ],
+        ),
+        // Store socket fd
+        BFLNode::Assign("fd".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+        // Create sockaddr_in structure (matches the server's bind address)
+        BFLNode::Assign("addr".to_string(), Box::new(BFLNode::Bytes(vec![
+            2, 0,     // AF_INET
+            31, 144,  // port 8080 (network byte order)
+            127, 0, 0, 1,  // 127.0.0.1
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0  // padding
+        ]))),
+        // Connect to the server
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("connect".to_string())),
+            vec![
+                BFLNode::Variable("fd".to_string()),
+                BFLNode::Variable("addr".to_string()),
+                BFLNode::Number(16), // addrlen
+            ],
+        ),
+        // Print connected message
+        BFLNode::Assign("connected_msg".to_string(), Box::new(BFLNode::Bytes(connected_msg.to_vec()))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("write".to_string())),
+            vec![
+                BFLNode::Number(1), // stdout
+                BFLNode::Variable("connected_msg".to_string()),
+                BFLNode::Number(connected_msg.len() as i32), // length
+            ],
+        ),
+        // Allocate buffer for the server's replies
+        BFLNode::Assign("buf".to_string(), Box::new(BFLNode::Bytes(vec![0; 1024]))),
+        // Send a greeting, then echo back whatever the server sends
+        BFLNode::Assign("hello_msg".to_string(), Box::new(BFLNode::Bytes(hello_msg.to_vec()))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("write".to_string())),
+            vec![
+                BFLNode::Variable("fd".to_string()),
+                BFLNode::Variable("hello_msg".to_string()),
+                BFLNode::Number(hello_msg.len() as i32),
+            ],
+        ),
+        // Main data loop: read from the server and print it to stdout
+        BFLNode::While(
+            Box::new(BFLNode::Number(1)), // infinite loop
+            vec![
+                // Read data from the server
+                BFLNode::Syscall(
+                    Box::new(BFLNode::String("read".to_string())),
+                    vec![
+                        BFLNode::Variable("fd".to_string()),
+                        BFLNode::Variable("buf".to_string()),
+                        BFLNode::Number(1024), // max bytes to read
+                    ],
+                ),
+                // Store bytes read
+                BFLNode::Assign("bytes_read".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+                // Print whatever the server sent back
+                BFLNode::If(
+                    Box::new(BFLNode::Variable("bytes_read".to_string())),
+                    vec![
+                        BFLNode::Syscall(
+                            Box::new(BFLNode::String("write".to_string())),
+                            vec![
+                                BFLNode::Number(1), // stdout
+                                BFLNode::Variable("buf".to_string()),
+                                BFLNode::Variable("bytes_read".to_string()),
+                            ],
+                        ),
+                    ]
+                ),
+            ]
+        ),
+        // Close the connection (unreachable)
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("close".to_string())),
+            vec![
+                BFLNode::Variable("fd".to_string()),
+            ],
+        ),
+    ]);
+
+    println!("Compiling ping-pong client...");
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    println!("Generated {} characters of brainfuck code", bf_code.len());
+    println!("Running ping-pong client...");
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    match bf.run() {
+        Ok(_) => println!("Client completed successfully"),
+        Err(e) => println!("Client error: {}", e),
+    }
+}
@@ -10,7 +10,7 @@ fn main() {
     let program = BFLNode::Block(vec![
         // Create socket
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(41)), // socket syscall
+            Box::new(BFLNode::String("socket".to_string())),
             vec![
                 BFLNode::Number(2),  // AF_INET
                 BFLNode::Number(1),  // SOCK_STREAM
@@ -30,7 +30,7 @@ fn main() {
         ]))),
         // Bind socket
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(49)), // bind syscall
+            Box::new(BFLNode::String("bind".to_string())),
             vec![
                 BFLNode::Variable("fd".to_string()),
                 BFLNode::Variable("addr".to_string()),
@@ -39,7 +39,7 @@ fn main() {
         ),
         // Listen for connections
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(50)), // listen syscall
+            Box::new(BFLNode::String("listen".to_string())),
             vec![
                 BFLNode::Variable("fd".to_string()),
                 BFLNode::Number(1), // backlog
@@ -49,7 +49,7 @@ fn main() {
         BFLNode::Assign("status_msg".to_string(), Box::new(BFLNode::Bytes(status_msg.to_vec()))),
         // Print status
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // write syscall
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1), // stdout
                 BFLNode::Variable("status_msg".to_string()),
@@ -58,7 +58,7 @@ fn main() {
         ),
         // Accept connection using server_fd (once, outside the data loop)
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(43)), // accept syscall
+            Box::new(BFLNode::String("accept".to_string())),
             vec![
                 BFLNode::Variable("server_fd".to_string()),
                 BFLNode::Number(0), // addr (NULL)
@@ -70,7 +70,7 @@ fn main() {
         // Print client connected message
         BFLNode::Assign("client_msg".to_string(), Box::new(BFLNode::Bytes(client_msg.to_vec()))),
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // write syscall
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1), // stdout
                 BFLNode::Variable("client_msg".to_string()),
@@ -86,7 +86,7 @@ fn main() {
                 // Print at start of loop
                 BFLNode::Assign("loop_msg".to_string(), Box::new(BFLNode::Bytes(b"[LOOP] Entered loop\n".to_vec()))),
                 BFLNode::Syscall(
-                    Box::new(BFLNode::Number(1)),
+                    Box::new(BFLNode::String("write".to_string())),
                     vec![
                         BFLNode::Number(1),
                         BFLNode::Variable("loop_msg".to_string()),
@@ -95,7 +95,7 @@ fn main() {
                 ),
                 // Read data from client
                 BFLNode::Syscall(
-                    Box::new(BFLNode::Number(0)), // read syscall
+                    Box::new(BFLNode::String("read".to_string())),
                     vec![
                         BFLNode::Variable("client_fd".to_string()),
                         BFLNode::Variable("buf".to_string()),
@@ -105,7 +105,7 @@ fn main() {
                 // Print after read syscall
                 BFLNode::Assign("after_read_msg".to_string(), Box::new(BFLNode::Bytes(b"[LOOP] After read\n".to_vec()))),
                 BFLNode::Syscall(
-                    Box::new(BFLNode::Number(1)),
+                    Box::new(BFLNode::String("write".to_string())),
                     vec![
                         BFLNode::Number(1),
                         BFLNode::Variable("after_read_msg".to_string()),
@@ -121,7 +121,7 @@ fn main() {
                         // Debug: Print a fixed message to show we received data
                         BFLNode::Assign("debug_msg".to_string(), Box::new(BFLNode::Bytes(b"Received data: ".to_vec()))),
                         BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
+                            Box::new(BFLNode::String("write".to_string())),
                             vec![
                                 BFLNode::Number(1), // stdout
                                 BFLNode::Variable("debug_msg".to_string()),
@@ -130,7 +130,7 @@ fn main() {
                         ),
                         // Debug: Print what we received to stdout
                         BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
+                            Box::new(BFLNode::String("write".to_string())),
                             vec![
                                 BFLNode::Number(1), // stdout
                                 BFLNode::Variable("buf".to_string()),
@@ -140,26 +140,19 @@ fn main() {
                         // Debug: Print bytes_read value as a decimal string
                         BFLNode::Assign("bytes_read_msg".to_string(), Box::new(BFLNode::Bytes(b"bytes_read: ".to_vec()))),
                         BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
+                            Box::new(BFLNode::String("write".to_string())),
                             vec![
                                 BFLNode::Number(1), // stdout
                                 BFLNode::Variable("bytes_read_msg".to_string()),
                                 BFLNode::Number(12), // length of "bytes_read: "
                             ],
                         ),
-                        // Print the value of bytes_read (as a single byte, not a full decimal string)
-                        BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
-                            vec![
-                                BFLNode::Number(1), // stdout
-                                BFLNode::Variable("bytes_read".to_string()),
-                                BFLNode::Number(1), // just print the raw byte value
-                            ],
-                        ),
+                        // Print the value of bytes_read as a decimal number
+                        BFLNode::PrintNumber(Box::new(BFLNode::Variable("bytes_read".to_string()))),
                         // Print newline
                         BFLNode::Assign("newline2".to_string(), Box::new(BFLNode::Bytes(b"\n".to_vec()))),
                         BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
+                            Box::new(BFLNode::String("write".to_string())),
                             vec![
                                 BFLNode::Number(1), // stdout
                                 BFLNode::Variable("newline2".to_string()),
@@ -168,7 +161,7 @@ fn main() {
                         ),
                         // Echo back the received data to client
                         BFLNode::Syscall(
-                            Box::new(BFLNode::Number(1)), // write syscall
+                            Box::new(BFLNode::String("write".to_string())),
                             vec![
                                 BFLNode::Variable("client_fd".to_string()),
                                 BFLNode::Variable("buf".to_string()),
@@ -181,14 +174,14 @@ fn main() {
         ),
         // Close client connection
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(3)), // close syscall
+            Box::new(BFLNode::String("close".to_string())),
             vec![
                 BFLNode::Variable("client_fd".to_string()),
             ],
         ),
         // Close server socket (unreachable)
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(3)), // close syscall
+            Box::new(BFLNode::String("close".to_string())),
             vec![
                 BFLNode::Variable("server_fd".to_string()),
             ],
@@ -8,7 +8,7 @@ fn main() {
     let program = BFLNode::Block(vec![
         // Create socket
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(41)), // socket syscall
+            Box::new(BFLNode::String("socket".to_string())),
             vec![
                 BFLNode::Number(2),  // AF_INET
                 BFLNode::Number(1),  // SOCK_STREAM
@@ -24,17 +24,17 @@ fn main() {
         
         // Print success message
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // write syscall
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1), // stdout
                 BFLNode::Variable("msg".to_string()),
                 BFLNode::Number(22), // length
             ],
         ),
-        
+
         // Close socket
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(3)), // close syscall
+            Box::new(BFLNode::String("close".to_string())),
             vec![
                 BFLNode::Variable("fd".to_string()),
             ],
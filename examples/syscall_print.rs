@@ -6,7 +6,7 @@ fn main() {
     let program = BFLNode::Block(vec![
         BFLNode::Assign("msg".to_string(), Box::new(BFLNode::String("Hello, syscall!\n".to_string()))),
         BFLNode::Syscall(
-            Box::new(BFLNode::Number(1)), // SYS_WRITE (Linux)
+            Box::new(BFLNode::String("write".to_string())),
             vec![
                 BFLNode::Number(1), // stdout
                 BFLNode::Variable("msg".to_string()), // pointer to string
@@ -0,0 +1,121 @@
+use ewor_brainfuck::bfl::{BFLCompiler, BFLNode};
+use ewor_brainfuck::bf::{BF, Mode};
+
+fn main() {
+    let mut compiler = BFLCompiler::new();
+
+    let status_msg = b"UDP echo listening on port 8080\n";
+
+    let program = BFLNode::Block(vec![
+        // Create a UDP (SOCK_DGRAM) socket
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("socket".to_string())),
+            vec![
+                BFLNode::Number(2), // AF_INET
+                BFLNode::Number(2), // SOCK_DGRAM
+                BFLNode::Number(0), // protocol
+            ],
+        ),
+        // Store socket fd
+        BFLNode::Assign("fd".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+        // Create sockaddr_in structure
+        BFLNode::Assign("addr".to_string(), Box::new(BFLNode::Bytes(vec![
+            2, 0,     // AF_INET
+            31, 144,  // port 8080 (network byte order)
+            127, 0, 0, 1,  // 127.0.0.1
+            0, 0, 0, 0, 0, 0, 0, 0  // padding
+        ]))),
+        // Bind socket
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("bind".to_string())),
+            vec![
+                BFLNode::Variable("fd".to_string()),
+                BFLNode::Variable("addr".to_string()),
+                BFLNode::Number(16), // addrlen
+            ],
+        ),
+        // Print status
+        BFLNode::Assign("status_msg".to_string(), Box::new(BFLNode::Bytes(status_msg.to_vec()))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("write".to_string())),
+            vec![
+                BFLNode::Number(1), // stdout
+                BFLNode::Variable("status_msg".to_string()),
+                BFLNode::Number(status_msg.len() as i32), // length
+            ],
+        ),
+        // Receive buffer, sized to a typical MTU-bounded datagram rather than
+        // the TCP examples' 1024 - UDP datagrams arrive whole, so there's no
+        // point sizing past what one Ethernet-framed packet can carry.
+        BFLNode::Assign("buf".to_string(), Box::new(BFLNode::Bytes(vec![0; 1500]))),
+        // Peer address `recvfrom` fills in, and its length as an in/out
+        // scalar - a 1-byte `Bytes` buffer so its cell value is an address
+        // the kernel can write the updated length back through, the same
+        // idiom `addr` itself uses to hand `bind` a pointer.
+        BFLNode::Assign("peer_addr".to_string(), Box::new(BFLNode::Bytes(vec![0; 16]))),
+        BFLNode::While(
+            Box::new(BFLNode::Number(1)), // infinite loop
+            vec![
+                BFLNode::Assign("peer_addrlen".to_string(), Box::new(BFLNode::Bytes(vec![16]))),
+                // Receive a datagram from whoever sends one
+                BFLNode::Syscall(
+                    Box::new(BFLNode::String("recvfrom".to_string())),
+                    vec![
+                        BFLNode::Variable("fd".to_string()),
+                        BFLNode::Variable("buf".to_string()),
+                        BFLNode::Number(1500), // max bytes to read
+                        BFLNode::Number(0),    // flags
+                        BFLNode::Variable("peer_addr".to_string()),
+                        BFLNode::Variable("peer_addrlen".to_string()),
+                    ],
+                ),
+                BFLNode::Assign("bytes_read".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+                // Echo the datagram straight back to the peer that sent it
+                BFLNode::If(
+                    Box::new(BFLNode::Variable("bytes_read".to_string())),
+                    vec![
+                        BFLNode::Syscall(
+                            Box::new(BFLNode::String("sendto".to_string())),
+                            vec![
+                                BFLNode::Variable("fd".to_string()),
+                                BFLNode::Variable("buf".to_string()),
+                                BFLNode::Variable("bytes_read".to_string()),
+                                BFLNode::Number(0), // flags
+                                BFLNode::Variable("peer_addr".to_string()),
+                                BFLNode::Number(16), // addrlen
+                            ],
+                        ),
+                    ]
+                ),
+            ]
+        ),
+        // Close socket (unreachable)
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("close".to_string())),
+            vec![
+                BFLNode::Variable("fd".to_string()),
+            ],
+        ),
+    ]);
+
+    println!("Compiling UDP echo server...");
+    println!("Program: {:?}", program);
+
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    // Print all variable addresses for debugging
+    for var in ["fd", "addr", "status_msg", "buf", "peer_addr", "peer_addrlen", "bytes_read"].iter() {
+        if let Some(addr) = compiler.get_variable_address(var) {
+            println!("[DEBUG] Variable {} address: {}", var, addr);
+        } else {
+            println!("[DEBUG] Variable {} not found", var);
+        }
+    }
+    println!("Generated {} characters of brainfuck code", bf_code.len());
+    println!("Running UDP echo server...");
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    match bf.run() {
+        Ok(_) => println!("Server completed successfully"),
+        Err(e) => println!("Server error: {}", e),
+    }
+}
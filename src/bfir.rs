@@ -0,0 +1,233 @@
+//! A small optimizing intermediate representation for generated BF, used in
+//! place of the old char-level peephole pass (adjacent `><`/`+-` and
+//! `[][]` cancellation, duplicated across `BFLCompiler`'s two "optimized
+//! output" methods). The pipeline is two stages: `parse` turns raw BF text
+//! into a `Vec<BfOp>`, run-length-coalescing consecutive `+`/`-` into
+//! `Add(n)` and `>`/`<` into `Move(n)`; `optimize` then runs structural
+//! recognizers over the IR - zeroing loops collapse to `SetZero`, and
+//! "copy/multiply loops" (a loop that only moves and adds, returns the
+//! pointer to where it started, and decrements its own cell by exactly one
+//! per iteration) collapse to a single `MulAdd`. `serialize` turns the
+//! result back into BF text. The IR is also the natural place for a future
+//! disassembler pass or an optimizing interpreter to hang off of (see
+//! `serialize`'s doc comment).
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use core::iter::Peekable;
+use core::str::Chars;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// One instruction in the IR. `Add`/`Move` use a full `i32`/`isize` rather
+/// than the single byte a classic 8-bit BF dialect would use: this
+/// compiler's cells are `u32` and genuinely don't wrap at 256 (see
+/// `bfl::BFLNode::WideNumber`'s doc comment), and a single coalesced run can already
+/// exceed a byte - e.g. `Bytes`'s data-pointer cell, which is set to an
+/// absolute memory address via one long run of `+`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BfOp {
+    Add(i32),
+    Move(isize),
+    /// `[-]` (or the `[+]` textbook variant, though this compiler never
+    /// emits that one - see `recognize_set_zero`): the current cell driven
+    /// to zero, regardless of its starting value.
+    SetZero,
+    /// The closed form of a "copy/multiply loop": every iteration adds a
+    /// constant amount to a fixed set of other cells and decrements the
+    /// current cell by exactly one, so after the loop the current cell is
+    /// zero and each `(offset, factor)` target has gained
+    /// `factor * original_value`. `copy_value`'s double-copy loop is the
+    /// `factor == 1` case of this.
+    MulAdd(Vec<(isize, u8)>),
+    Out,
+    In,
+    Loop(Vec<BfOp>),
+}
+
+/// Parses raw BF source into an IR tree, coalescing consecutive `+`/`-`
+/// into a single `Add` and `>`/`<` into a single `Move`. The BF this
+/// compiler emits is always balanced (it's generated, never user-supplied),
+/// so an unbalanced `[` is an internal bug, not a reportable user error -
+/// this panics rather than returning a `Result`, mirroring the rest of this
+/// pipeline (`optimize_output` has never had a fallible contract either).
+pub fn parse(src: &str) -> Vec<BfOp> {
+    let mut chars = src.chars().peekable();
+    let ops = parse_block(&mut chars);
+    assert!(chars.next().is_none(), "unbalanced ']' in generated BF");
+    ops
+}
+
+fn parse_block(chars: &mut Peekable<Chars>) -> Vec<BfOp> {
+    let mut ops = Vec::new();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '+' | '-' => {
+                let mut n: i32 = 0;
+                while let Some(&c2) = chars.peek() {
+                    match c2 {
+                        '+' => n += 1,
+                        '-' => n -= 1,
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                if n != 0 {
+                    ops.push(BfOp::Add(n));
+                }
+            }
+            '>' | '<' => {
+                let mut n: isize = 0;
+                while let Some(&c2) = chars.peek() {
+                    match c2 {
+                        '>' => n += 1,
+                        '<' => n -= 1,
+                        _ => break,
+                    }
+                    chars.next();
+                }
+                if n != 0 {
+                    ops.push(BfOp::Move(n));
+                }
+            }
+            '.' => {
+                chars.next();
+                ops.push(BfOp::Out);
+            }
+            ',' => {
+                chars.next();
+                ops.push(BfOp::In);
+            }
+            '[' => {
+                chars.next();
+                let body = parse_block(chars);
+                assert_eq!(chars.next(), Some(']'), "unbalanced '[' in generated BF");
+                ops.push(BfOp::Loop(body));
+            }
+            ']' => break,
+            _ => {
+                chars.next();
+            }
+        }
+    }
+    ops
+}
+
+/// Runs the IR's structural recognizers (`SetZero`, `MulAdd`) bottom-up, so
+/// a loop's body is optimized before the loop itself is considered for
+/// rewriting.
+pub fn optimize(ops: Vec<BfOp>) -> Vec<BfOp> {
+    ops.into_iter()
+        .map(|op| match op {
+            BfOp::Loop(body) => optimize_loop(optimize(body)),
+            other => other,
+        })
+        .collect()
+}
+
+fn optimize_loop(body: Vec<BfOp>) -> BfOp {
+    if recognize_set_zero(&body) {
+        return BfOp::SetZero;
+    }
+    if let Some(mul_add) = recognize_mul_add(&body) {
+        return mul_add;
+    }
+    BfOp::Loop(body)
+}
+
+/// `[-]`: a loop body that's nothing but a single decrement of the current
+/// cell. This always reaches zero in exactly the cell's starting-value
+/// iterations. The `[+]` textbook counterpart isn't recognized here: on an
+/// 8-bit wrapping cell it also reaches zero eventually, but this
+/// interpreter's cells are `u32` and don't wrap, so `[+]` on a nonzero cell
+/// never terminates - this compiler never emits it, and "recognizing" it
+/// would silently change an infinite loop into a no-op.
+fn recognize_set_zero(body: &[BfOp]) -> bool {
+    matches!(body, [BfOp::Add(-1)])
+}
+
+/// Recognizes a "copy/multiply loop": a body of only `Move`/`Add` ops that
+/// returns the pointer to where it started and decrements its own cell
+/// (offset 0) by exactly one per iteration. Every other offset touched must
+/// have a net positive per-iteration delta that fits in a `u8` - the only
+/// shape `copy_value`'s `[->+<]`-style loops (and everything derived from
+/// them) ever produce. Anything else (a loop with `In`/`Out`/nested loops,
+/// one that doesn't return to its start, or has a non-unit source
+/// decrement) is left alone.
+fn recognize_mul_add(body: &[BfOp]) -> Option<BfOp> {
+    let mut pointer: isize = 0;
+    let mut deltas: HashMap<isize, i32> = HashMap::new();
+    for op in body {
+        match op {
+            BfOp::Move(n) => pointer += n,
+            BfOp::Add(n) => *deltas.entry(pointer).or_insert(0) += n,
+            _ => return None,
+        }
+    }
+    if pointer != 0 {
+        return None;
+    }
+    if deltas.remove(&0) != Some(-1) {
+        return None;
+    }
+    let mut targets = Vec::with_capacity(deltas.len());
+    for (offset, amount) in deltas {
+        if amount <= 0 || amount > u8::MAX as i32 {
+            return None;
+        }
+        targets.push((offset, amount as u8));
+    }
+    targets.sort_by_key(|&(offset, _)| offset);
+    Some(BfOp::MulAdd(targets))
+}
+
+/// Turns IR back into BF text. `MulAdd` re-expands into an equivalent
+/// `[...]` loop - raw BF has no way to read a cell's value and scale it in
+/// one step, so the loop itself can't be eliminated from the *text*; the
+/// `Vec<BfOp>` form is what lets a future consumer (an optimizing
+/// interpreter, or the disassembler) skip the per-iteration cost instead.
+pub fn serialize(ops: &[BfOp]) -> String {
+    let mut out = String::new();
+    serialize_into(ops, &mut out);
+    out
+}
+
+fn serialize_into(ops: &[BfOp], out: &mut String) {
+    for op in ops {
+        match op {
+            BfOp::Add(n) => push_signed(out, '+', '-', *n as isize),
+            BfOp::Move(n) => push_signed(out, '>', '<', *n),
+            BfOp::SetZero => out.push_str("[-]"),
+            BfOp::MulAdd(targets) => {
+                out.push('[');
+                for &(offset, factor) in targets {
+                    push_signed(out, '>', '<', offset);
+                    out.push_str(&"+".repeat(factor as usize));
+                    push_signed(out, '>', '<', -offset);
+                }
+                out.push('-');
+                out.push(']');
+            }
+            BfOp::Out => out.push('.'),
+            BfOp::In => out.push(','),
+            BfOp::Loop(body) => {
+                out.push('[');
+                serialize_into(body, out);
+                out.push(']');
+            }
+        }
+    }
+}
+
+fn push_signed(out: &mut String, positive: char, negative: char, n: isize) {
+    if n > 0 {
+        out.push_str(&positive.to_string().repeat(n as usize));
+    } else if n < 0 {
+        out.push_str(&negative.to_string().repeat((-n) as usize));
+    }
+}
@@ -1,8 +1,211 @@
+#[cfg(feature = "std")]
 use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+#[cfg(not(feature = "std"))]
+use alloc::vec;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::graph::Graph;
+use crate::syscall_consts::*;
 
 // Use cells closer to variables for better efficiency
 const SCRATCH_1: usize = 100;
 const SCRATCH_2: usize = 101; // Adjacent to SCRATCH_1 for efficient copying
+/// Extra scratch cells for `Mul`/`Div`/`Mod` and the comparison/logical
+/// operators, which need more temporaries at once than `SCRATCH_1`/
+/// `SCRATCH_2` alone can hold (an operand pair plus working cells for
+/// clamped-subtraction-based comparison). `SCRATCH_7`/`SCRATCH_8` are
+/// general disposable temporaries within that codegen, the same role
+/// `SCRATCH_1` plays for `copy_value`.
+const SCRATCH_3: usize = 102;
+const SCRATCH_4: usize = 103;
+const SCRATCH_5: usize = 104;
+const SCRATCH_6: usize = 105;
+const SCRATCH_7: usize = 106;
+const SCRATCH_8: usize = 107;
+/// First cell `allocate_cells` hands out to a colored variable. Below this
+/// are the reserved syscall-argument cells (0-7); above the colored region,
+/// `Bytes`/`String` data keeps bumping forward from `next_var_location`.
+const BASE_VAR_CELL: usize = 8;
+/// First cell of the disposable scratch region `WideAdd`/`WideSub` use to
+/// hold the evaluated RHS limbs plus their carry-propagation machinery:
+/// `width` limb cells, then one cell each for `carry`, `tmp`, `counter`,
+/// `bound`, and `flag` (see `compile_wide_merge`/`emit_wide_limb_add`).
+/// Sized per call to the operation's own `width`, not reserved up front -
+/// there's only ever one wide operation "in flight" at a time, since
+/// codegen is single-threaded and sequential.
+const WIDE_SCRATCH_BASE: usize = 108;
+/// First cell of a scratch region indexed by `scratch_depth` rather than
+/// fixed in place like `SCRATCH_3`/`SCRATCH_4`. `Mul`, `compile_diffs`
+/// (`Eq`/`Neq`/`Lt`/`Gt`), `compile_divmod` (`Div`/`Mod`), and `And`/`Or`
+/// all evaluate one operand, need that value to survive while the other
+/// operand is evaluated, and only then combine them - if the second
+/// operand is itself one of these same compound expressions, it would
+/// otherwise reuse the very fixed cell the first operand's value is
+/// still sitting in. Giving that "held" value a fresh cell per nesting
+/// depth (see `held_operand_cell`) keeps a nested compound operand from
+/// aliasing the one its parent is still holding onto. Sized generously
+/// since `BF::new` allocates 65536 cells and realistic expression nesting
+/// never comes close.
+const HELD_OPERAND_SCRATCH_BASE: usize = 512;
+
+/// The symbolic names `BFLNode::Syscall` resolves out of the box, mirroring
+/// `BF::register_default_syscalls`. Custom host functions can be added with
+/// `BFLCompiler::register_syscall_name`.
+fn default_syscall_names() -> HashMap<String, u32> {
+    let mut names = HashMap::new();
+    names.insert("read".to_string(), SYS_READ as u32);
+    names.insert("write".to_string(), SYS_WRITE as u32);
+    names.insert("close".to_string(), SYS_CLOSE as u32);
+    names.insert("socket".to_string(), SYS_SOCKET as u32);
+    names.insert("bind".to_string(), SYS_BIND as u32);
+    names.insert("listen".to_string(), SYS_LISTEN as u32);
+    names.insert("accept".to_string(), SYS_ACCEPT as u32);
+    names.insert("connect".to_string(), SYS_CONNECT as u32);
+    names.insert("readv".to_string(), SYS_READV as u32);
+    names.insert("writev".to_string(), SYS_WRITEV as u32);
+    names.insert("sha256".to_string(), SYS_SHA256 as u32);
+    names.insert("keccak256".to_string(), SYS_KECCAK256 as u32);
+    names.insert("blake3".to_string(), SYS_BLAKE3 as u32);
+    names.insert("secp256k1_recover".to_string(), SYS_SECP256K1_RECOVER as u32);
+    names.insert("peek".to_string(), SYS_PEEK as u32);
+    names.insert("poke".to_string(), SYS_POKE as u32);
+    names.insert("poll".to_string(), SYS_POLL as u32);
+    names.insert("sendto".to_string(), SYS_SENDTO as u32);
+    names.insert("recvfrom".to_string(), SYS_RECVFROM as u32);
+    names
+}
+
+/// A location `analyze` attaches to a diagnostic. BFL's AST is hand-built
+/// rather than parsed from text, so there's no byte offset to report; a span
+/// is just the order in which `analyze` encountered the offending node,
+/// enough to tell two findings apart and to search the AST for the right one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub node_index: usize,
+}
+
+/// A semantic error `analyze` found before codegen, mirroring `compile`'s
+/// own silent failure modes with a typed, located diagnostic instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BflError {
+    /// A syscall's literal length argument is longer than the `Bytes`/`String`
+    /// buffer the preceding argument names (the `test_bfl_printf` case: `12`
+    /// must not exceed `"Hello, BFL!\n"`'s length).
+    BufferLengthMismatch { span: Span, declared: usize, used: usize },
+    /// `Add`/`Sub` mixed a `Number` operand with a `String`/`Bytes` one.
+    TypeMismatch { span: Span, expected: &'static str, found: &'static str },
+    /// A `Variable` node named something nothing ever assigned.
+    UndefinedVariable { span: Span, name: String },
+}
+
+impl core::fmt::Display for BflError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BflError::BufferLengthMismatch { span, declared, used } => write!(
+                f,
+                "[{span:?}] syscall length {used} exceeds the buffer's declared {declared} bytes"
+            ),
+            BflError::TypeMismatch { span, expected, found } => {
+                write!(f, "[{span:?}] expected a {expected} operand, found {found}")
+            }
+            BflError::UndefinedVariable { span, name } => {
+                write!(f, "[{span:?}] variable '{name}' is never assigned")
+            }
+        }
+    }
+}
+
+impl core::error::Error for BflError {}
+
+/// One record in `BFLCompiler`'s disassembly side table: the raw-BF byte
+/// range a single top-level `BFLNode` emitted, plus a human-readable
+/// description of what it was (e.g. "Assign msg -> cell 8") and how many
+/// `While`/`If` bodies deep it's nested. Modeled on the `holey-bytes`
+/// disassembler's `DisasmItem` - a flat, renderable log of what codegen did
+/// and when, rather than a structured AST.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisasmItem {
+    pub start: usize,
+    pub end: usize,
+    pub description: String,
+    pub depth: usize,
+}
+
+/// Why a disassembly lookup failed - mirrors `holey-bytes`'s `DisasmError`
+/// in spirit: a typed reason, not just an `Option`/`bool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisasmError {
+    /// `offset` is past the end of the compiled program (`len` bytes).
+    OffsetOutOfRange { offset: usize, len: usize },
+    /// `offset` is a valid program offset, but it falls in a stretch of
+    /// output (pointer-movement glue, scratch-cell bookkeeping) that no
+    /// recorded span covers.
+    NoSpanFound { offset: usize },
+}
+
+impl core::fmt::Display for DisasmError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DisasmError::OffsetOutOfRange { offset, len } => {
+                write!(f, "offset {offset} is past the end of the {len}-byte program")
+            }
+            DisasmError::NoSpanFound { offset } => {
+                write!(f, "offset {offset} isn't covered by any recorded span")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DisasmError {}
+
+/// The inferred kind of a value-producing node, just precise enough to spot
+/// a `String`/`Bytes` operand mixed into arithmetic with a `Number`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ValueKind {
+    Number,
+    Buffer,
+}
+
+fn infer_kind(node: &BFLNode, buffer_lens: &HashMap<String, usize>) -> Option<ValueKind> {
+    match node {
+        BFLNode::Number(_) => Some(ValueKind::Number),
+        BFLNode::String(_) | BFLNode::Bytes(_) => Some(ValueKind::Buffer),
+        BFLNode::Variable(name) => buffer_lens.contains_key(name).then_some(ValueKind::Buffer),
+        BFLNode::Add(lhs, _) | BFLNode::Sub(lhs, _) => infer_kind(lhs, buffer_lens),
+        _ => None,
+    }
+}
+
+/// State threaded through `BFLCompiler::analyze`'s traversal - separate from
+/// `BFLCompiler`'s own fields so analysis never mutates the compiler it ran on.
+struct AnalysisCtx {
+    assigned: HashSet<String>,
+    buffer_lens: HashMap<String, usize>,
+    next_index: usize,
+    errors: Vec<BflError>,
+}
+
+impl AnalysisCtx {
+    fn span(&mut self) -> Span {
+        let span = Span { node_index: self.next_index };
+        self.next_index += 1;
+        span
+    }
+}
 
 #[derive(Debug, Clone)]
 pub enum BFLNode {
@@ -13,10 +216,349 @@ pub enum BFLNode {
     Bytes(Vec<u8>),
     Add(Box<BFLNode>, Box<BFLNode>),
     Sub(Box<BFLNode>, Box<BFLNode>),
+    Mul(Box<BFLNode>, Box<BFLNode>),
+    Div(Box<BFLNode>, Box<BFLNode>),
+    Mod(Box<BFLNode>, Box<BFLNode>),
+    /// Equality: 1 if both operands hold the same value, else 0.
+    Eq(Box<BFLNode>, Box<BFLNode>),
+    /// Inequality: 1 if the operands differ, else 0.
+    Neq(Box<BFLNode>, Box<BFLNode>),
+    /// Less-than: 1 if the left operand is strictly smaller, else 0.
+    Lt(Box<BFLNode>, Box<BFLNode>),
+    /// Greater-than: 1 if the left operand is strictly larger, else 0.
+    Gt(Box<BFLNode>, Box<BFLNode>),
+    /// Logical AND: 1 if both operands are nonzero, else 0.
+    And(Box<BFLNode>, Box<BFLNode>),
+    /// Logical OR: 1 if either operand is nonzero, else 0.
+    Or(Box<BFLNode>, Box<BFLNode>),
+    /// Logical NOT: 1 if the operand is zero, else 0.
+    Not(Box<BFLNode>),
     If(Box<BFLNode>, Vec<BFLNode>),
     While(Box<BFLNode>, Vec<BFLNode>),
     Syscall(Box<BFLNode>, Vec<BFLNode>),
     Block(Vec<BFLNode>),
+    /// A little-endian, base-256 wide integer literal spanning `width`
+    /// cells - `Number`'s counterpart for values too big for one digit.
+    /// `BF`'s own cells don't wrap at 256 (they're `u32`), so this imposes
+    /// byte-wide arithmetic explicitly rather than relying on cell width.
+    WideNumber(u64, usize),
+    /// Adds two `width`-limb wide integers, propagating carry limb by limb.
+    /// Each operand must be a `WideNumber` or a `Variable` previously
+    /// assigned from one of the same width - see
+    /// `BFLCompiler::compile_wide_merge`.
+    WideAdd(Box<BFLNode>, Box<BFLNode>, usize),
+    /// The borrowing mirror of `WideAdd`, via 255's-complement-plus-one.
+    WideSub(Box<BFLNode>, Box<BFLNode>, usize),
+    /// Prints the operand's value as a decimal string (no leading zeroes,
+    /// `0` prints as `"0"`) - desugared at `compile_node` time into a
+    /// fixed-width divmod-by-10 extraction plus one `write` syscall per
+    /// digit, since there's no indirect addressing to drive a variable-sized
+    /// digit stack. See `desugar_print_number`.
+    PrintNumber(Box<BFLNode>),
+    /// Reads the byte at `buffer[offset]` - both operands are plain
+    /// expressions, typically a `Variable` naming a `Bytes`/`String` buffer
+    /// (whose value is the buffer's base address) and a `Variable` holding a
+    /// runtime offset. Raw BF has no indirect addressing (`move_to` targets
+    /// are always compile-time constants), so this is desugared into a
+    /// `peek` syscall over `buffer + offset` rather than a real pointer
+    /// dereference - see `desugar_index`. `IndexAssign` is the lvalue
+    /// counterpart.
+    Index(Box<BFLNode>, Box<BFLNode>),
+    /// Writes `value` to `buffer[offset]` - the statement-position mirror of
+    /// `Index`, desugared into a `poke` syscall the same way.
+    IndexAssign(Box<BFLNode>, Box<BFLNode>, Box<BFLNode>),
+    /// Polls `nfds` `struct pollfd` entries packed into `fds` (a `Bytes`
+    /// buffer, 8 bytes per entry) for readiness, blocking up to
+    /// `timeout_ms`. Desugars into a `poll` syscall over `fds` and
+    /// `nfds * 8` - see `desugar_poll`. The number of ready fds lands in
+    /// `_syscall_result`, same convention as every other `Syscall`.
+    Poll(Box<BFLNode>, Box<BFLNode>, Box<BFLNode>),
+    /// True if `expr` (typically `_syscall_result`) is a syscall's encoded
+    /// failure, i.e. `expr >= SYSCALL_ERROR_BASE` - see `desugar_is_error`.
+    IsError(Box<BFLNode>),
+    /// Recovers the raw errno a failed syscall encoded into `expr`, i.e.
+    /// `expr - SYSCALL_ERROR_BASE`. Only meaningful once `IsError(expr)` has
+    /// confirmed the call actually failed - see `desugar_errno`.
+    Errno(Box<BFLNode>),
+}
+
+/// Every name `node` reads or writes, anywhere in its subtree - used to
+/// widen a variable's live range to span an entire loop when it's touched
+/// inside one.
+fn collect_names(node: &BFLNode, names: &mut HashSet<String>) {
+    match node {
+        BFLNode::Assign(name, expr) => {
+            names.insert(name.clone());
+            collect_names(expr, names);
+        }
+        BFLNode::Variable(name) => {
+            names.insert(name.clone());
+        }
+        BFLNode::Add(lhs, rhs)
+        | BFLNode::Sub(lhs, rhs)
+        | BFLNode::Mul(lhs, rhs)
+        | BFLNode::Div(lhs, rhs)
+        | BFLNode::Mod(lhs, rhs)
+        | BFLNode::Eq(lhs, rhs)
+        | BFLNode::Neq(lhs, rhs)
+        | BFLNode::Lt(lhs, rhs)
+        | BFLNode::Gt(lhs, rhs)
+        | BFLNode::And(lhs, rhs)
+        | BFLNode::Or(lhs, rhs)
+        | BFLNode::WideAdd(lhs, rhs, _)
+        | BFLNode::WideSub(lhs, rhs, _) => {
+            collect_names(lhs, names);
+            collect_names(rhs, names);
+        }
+        BFLNode::Not(operand) => collect_names(operand, names),
+        BFLNode::If(cond, body) | BFLNode::While(cond, body) => {
+            collect_names(cond, names);
+            for stmt in body {
+                collect_names(stmt, names);
+            }
+        }
+        BFLNode::Syscall(syscall_no, args) => {
+            if !matches!(syscall_no.as_ref(), BFLNode::String(_)) {
+                collect_names(syscall_no, names);
+            }
+            for arg in args {
+                collect_names(arg, names);
+            }
+        }
+        BFLNode::Block(stmts) => {
+            for stmt in stmts {
+                collect_names(stmt, names);
+            }
+        }
+        BFLNode::PrintNumber(expr) => collect_names(expr, names),
+        BFLNode::Index(buffer, offset) => {
+            collect_names(buffer, names);
+            collect_names(offset, names);
+        }
+        BFLNode::IndexAssign(buffer, offset, value) => {
+            collect_names(buffer, names);
+            collect_names(offset, names);
+            collect_names(value, names);
+        }
+        BFLNode::Poll(fds, nfds, timeout_ms) => {
+            collect_names(fds, names);
+            collect_names(nfds, names);
+            collect_names(timeout_ms, names);
+        }
+        BFLNode::IsError(expr) | BFLNode::Errno(expr) => collect_names(expr, names),
+        BFLNode::String(_) | BFLNode::Number(_) | BFLNode::Bytes(_) | BFLNode::WideNumber(_, _) => {}
+    }
+}
+
+/// The `width` a `WideNumber`/`WideAdd`/`WideSub` expression declares, or
+/// `None` for anything else - `allocate_cells` uses this to keep a wide
+/// result's variable out of the single-cell interference-graph coloring,
+/// and `compile_node`'s `Assign` arm uses it to bump-allocate that many
+/// cells instead of asking the colorer for one.
+fn wide_width(expr: &BFLNode) -> Option<usize> {
+    match expr {
+        BFLNode::WideNumber(_, width) => Some(*width),
+        BFLNode::WideAdd(_, _, width) | BFLNode::WideSub(_, _, width) => Some(*width),
+        _ => None,
+    }
+}
+
+/// Every variable name anywhere in `node` that's assigned directly from a
+/// `WideNumber`/`WideAdd`/`WideSub` - excluded from `allocate_cells`'s
+/// coloring since it needs `width` contiguous cells of its own, not a
+/// shared single color.
+fn wide_variable_names(node: &BFLNode) -> HashSet<String> {
+    let mut names = HashSet::new();
+    collect_wide_names(node, &mut names);
+    names
+}
+
+fn collect_wide_names(node: &BFLNode, names: &mut HashSet<String>) {
+    match node {
+        BFLNode::Block(stmts) => {
+            for stmt in stmts {
+                collect_wide_names(stmt, names);
+            }
+        }
+        BFLNode::Assign(name, expr) => {
+            if wide_width(expr).is_some() {
+                names.insert(name.clone());
+            }
+            collect_wide_names(expr, names);
+        }
+        BFLNode::If(cond, body) | BFLNode::While(cond, body) => {
+            collect_wide_names(cond, names);
+            for stmt in body {
+                collect_wide_names(stmt, names);
+            }
+        }
+        BFLNode::Syscall(syscall_no, args) => {
+            collect_wide_names(syscall_no, names);
+            for arg in args {
+                collect_wide_names(arg, names);
+            }
+        }
+        BFLNode::Add(lhs, rhs)
+        | BFLNode::Sub(lhs, rhs)
+        | BFLNode::Mul(lhs, rhs)
+        | BFLNode::Div(lhs, rhs)
+        | BFLNode::Mod(lhs, rhs)
+        | BFLNode::Eq(lhs, rhs)
+        | BFLNode::Neq(lhs, rhs)
+        | BFLNode::Lt(lhs, rhs)
+        | BFLNode::Gt(lhs, rhs)
+        | BFLNode::And(lhs, rhs)
+        | BFLNode::Or(lhs, rhs)
+        | BFLNode::WideAdd(lhs, rhs, _)
+        | BFLNode::WideSub(lhs, rhs, _) => {
+            collect_wide_names(lhs, names);
+            collect_wide_names(rhs, names);
+        }
+        BFLNode::Not(operand) => collect_wide_names(operand, names),
+        BFLNode::PrintNumber(expr) => collect_wide_names(expr, names),
+        BFLNode::Index(buffer, offset) => {
+            collect_wide_names(buffer, names);
+            collect_wide_names(offset, names);
+        }
+        BFLNode::IndexAssign(buffer, offset, value) => {
+            collect_wide_names(buffer, names);
+            collect_wide_names(offset, names);
+            collect_wide_names(value, names);
+        }
+        BFLNode::Poll(fds, nfds, timeout_ms) => {
+            collect_wide_names(fds, names);
+            collect_wide_names(nfds, names);
+            collect_wide_names(timeout_ms, names);
+        }
+        BFLNode::IsError(expr) | BFLNode::Errno(expr) => collect_wide_names(expr, names),
+        BFLNode::Variable(_)
+        | BFLNode::String(_)
+        | BFLNode::Number(_)
+        | BFLNode::Bytes(_)
+        | BFLNode::WideNumber(_, _) => {}
+    }
+}
+
+/// The disposable scratch cells `emit_wide_limb_add` needs beyond the limb
+/// pair it operates on - bundled into one struct rather than five positional
+/// `usize` parameters. See `WIDE_SCRATCH_BASE` for how these are laid out
+/// relative to the evaluated RHS limbs.
+#[derive(Clone, Copy)]
+struct WideScratch {
+    carry: usize,
+    tmp: usize,
+    counter: usize,
+    bound: usize,
+    flag: usize,
+}
+
+fn touch_range(ranges: &mut HashMap<String, (usize, usize)>, name: &str, step: usize) {
+    ranges
+        .entry(name.to_string())
+        .and_modify(|(first, last)| {
+            *first = (*first).min(step);
+            *last = (*last).max(step);
+        })
+        .or_insert((step, step));
+}
+
+/// Walks `node` in the same order `compile` will, stamping each variable
+/// read/write with a linear "step" counter. An `If`/`While`'s condition and
+/// body get a `[loop_start, loop_end)` step range; every name touched
+/// anywhere inside is then widened to span that whole range, since it stays
+/// live for the loop's duration regardless of where inside it it's used.
+fn visit_live(node: &BFLNode, step: &mut usize, ranges: &mut HashMap<String, (usize, usize)>) {
+    match node {
+        BFLNode::Block(stmts) => {
+            for stmt in stmts {
+                visit_live(stmt, step, ranges);
+            }
+        }
+        BFLNode::Assign(name, expr) => {
+            visit_live(expr, step, ranges);
+            touch_range(ranges, name, *step);
+            *step += 1;
+        }
+        BFLNode::Variable(name) => {
+            touch_range(ranges, name, *step);
+            *step += 1;
+        }
+        BFLNode::Add(lhs, rhs)
+        | BFLNode::Sub(lhs, rhs)
+        | BFLNode::Mul(lhs, rhs)
+        | BFLNode::Div(lhs, rhs)
+        | BFLNode::Mod(lhs, rhs)
+        | BFLNode::Eq(lhs, rhs)
+        | BFLNode::Neq(lhs, rhs)
+        | BFLNode::Lt(lhs, rhs)
+        | BFLNode::Gt(lhs, rhs)
+        | BFLNode::And(lhs, rhs)
+        | BFLNode::Or(lhs, rhs) => {
+            visit_live(lhs, step, ranges);
+            visit_live(rhs, step, ranges);
+        }
+        BFLNode::Not(operand) => visit_live(operand, step, ranges),
+        BFLNode::If(cond, body) | BFLNode::While(cond, body) => {
+            let loop_start = *step;
+            visit_live(cond, step, ranges);
+            for stmt in body {
+                visit_live(stmt, step, ranges);
+            }
+            let loop_end = *step;
+            let mut touched = HashSet::new();
+            collect_names(node, &mut touched);
+            for name in touched {
+                touch_range(ranges, &name, loop_start);
+                touch_range(ranges, &name, loop_end);
+            }
+            *step += 1;
+        }
+        BFLNode::Syscall(syscall_no, args) => {
+            if !matches!(syscall_no.as_ref(), BFLNode::String(_)) {
+                visit_live(syscall_no, step, ranges);
+            }
+            for arg in args {
+                visit_live(arg, step, ranges);
+            }
+            *step += 1;
+        }
+        BFLNode::WideAdd(lhs, rhs, _) | BFLNode::WideSub(lhs, rhs, _) => {
+            visit_live(lhs, step, ranges);
+            visit_live(rhs, step, ranges);
+        }
+        BFLNode::PrintNumber(expr) => {
+            visit_live(expr, step, ranges);
+            *step += 1;
+        }
+        BFLNode::Index(buffer, offset) => {
+            visit_live(buffer, step, ranges);
+            visit_live(offset, step, ranges);
+        }
+        BFLNode::IndexAssign(buffer, offset, value) => {
+            visit_live(buffer, step, ranges);
+            visit_live(offset, step, ranges);
+            visit_live(value, step, ranges);
+            *step += 1;
+        }
+        BFLNode::Poll(fds, nfds, timeout_ms) => {
+            visit_live(fds, step, ranges);
+            visit_live(nfds, step, ranges);
+            visit_live(timeout_ms, step, ranges);
+            *step += 1;
+        }
+        BFLNode::IsError(expr) | BFLNode::Errno(expr) => visit_live(expr, step, ranges),
+        BFLNode::Number(_) | BFLNode::String(_) | BFLNode::Bytes(_) | BFLNode::WideNumber(_, _) => {}
+    }
+}
+
+/// The first and last program-order step at which each variable in `node`
+/// is read or written - `allocate_cells`'s interference graph is built from
+/// these ranges' overlaps.
+fn live_ranges(node: &BFLNode) -> HashMap<String, (usize, usize)> {
+    let mut ranges = HashMap::new();
+    let mut step = 0usize;
+    visit_live(node, &mut step, &mut ranges);
+    ranges
 }
 
 pub struct BFLCompiler {
@@ -24,6 +566,24 @@ pub struct BFLCompiler {
     next_var_location: usize,
     output: String,
     current_ptr: usize,
+    /// Resolves `BFLNode::Syscall`'s named form to a number at compile time;
+    /// unknown names are a clean compile error instead of undefined behavior.
+    syscall_names: HashMap<String, u32>,
+    /// `start_addr -> byte_len` for each `Bytes`/`String` allocation, handed
+    /// to `BF::with_memory_regions` so the interpreter can reject a syscall
+    /// length that overruns the buffer it names.
+    regions: HashMap<usize, usize>,
+    /// One entry per top-level statement `compile_node` emitted, for
+    /// `disasm`/`render_annotated`/`node_at_offset`.
+    spans: Vec<DisasmItem>,
+    /// How many `While`/`If` bodies deep the next emitted span is nested -
+    /// `DisasmItem::depth` for `render_annotated`'s indentation.
+    loop_depth: usize,
+    /// How many compound-expression "held operand" cells are currently
+    /// live, i.e. how many `Mul`/`compile_diffs`/`compile_divmod`/`And`/`Or`
+    /// calls are on the Rust call stack right now. See
+    /// `HELD_OPERAND_SCRATCH_BASE`/`held_operand_cell`.
+    scratch_depth: usize,
 }
 
 impl Default for BFLCompiler {
@@ -39,12 +599,35 @@ impl BFLCompiler {
         variables.insert("_syscall_result".to_string(), 0);
         BFLCompiler {
             variables,
-            next_var_location: 8, // Start user variables after syscall reserved area
+            next_var_location: BASE_VAR_CELL, // Start user variables after syscall reserved area
             output: String::new(),
             current_ptr: 0,
+            syscall_names: default_syscall_names(),
+            regions: HashMap::new(),
+            spans: Vec::new(),
+            loop_depth: 0,
+            scratch_depth: 0,
         }
     }
 
+    /// The cell reserved for whichever compound expression currently has
+    /// an operand's value "held" while it evaluates its other operand -
+    /// see `HELD_OPERAND_SCRATCH_BASE`. Callers capture this once, before
+    /// evaluating their held operand, and use the captured value (not a
+    /// fresh call) after bumping `scratch_depth` back down, so it keeps
+    /// pointing at the same cell regardless of what a nested call does to
+    /// `scratch_depth` in the meantime.
+    fn held_operand_cell(&self) -> usize {
+        HELD_OPERAND_SCRATCH_BASE + self.scratch_depth
+    }
+
+    /// Registers a symbolic name for a syscall number so `BFLNode::Syscall`
+    /// can reference a custom host function by name instead of a bare
+    /// number. Mirrors `BF::register_named_syscall` on the runtime side.
+    pub fn register_syscall_name(&mut self, name: &str, num: u32) {
+        self.syscall_names.insert(name.to_string(), num);
+    }
+
     // A clean, simple, and correct pointer movement function.
     fn move_to(&mut self, target: usize) {
         if self.current_ptr == target {
@@ -89,10 +672,16 @@ impl BFLCompiler {
             return;
         }
 
+        // Needs a temp distinct from both src and dest - callers occasionally
+        // pass SCRATCH_1 itself as dest (e.g. Add/Sub evaluating their RHS
+        // operand there), which would otherwise have this function clobber
+        // its own working cell. Fall back to SCRATCH_8 in that case.
+        let scratch = if dest == SCRATCH_1 { SCRATCH_8 } else { SCRATCH_1 };
+
         // 1. Clear destination and scratch cell
         self.move_to(dest);
         self.output.push_str("[-]");
-        self.move_to(SCRATCH_1);
+        self.move_to(scratch);
         self.output.push_str("[-]");
 
         // 2. Move value from src to dest and scratch
@@ -100,18 +689,18 @@ impl BFLCompiler {
         self.output.push_str("["); // while src is not zero
         self.move_to(dest);
         self.output.push('+'); // dest++
-        self.move_to(SCRATCH_1);
+        self.move_to(scratch);
         self.output.push('+'); // scratch++
         self.move_to(src);
         self.output.push('-'); // src--
         self.output.push_str("]");
 
         // 3. Restore value from scratch to src
-        self.move_to(SCRATCH_1);
+        self.move_to(scratch);
         self.output.push_str("["); // while scratch is not zero
         self.move_to(src);
         self.output.push('+'); // src++
-        self.move_to(SCRATCH_1);
+        self.move_to(scratch);
         self.output.push('-'); // scratch--
         self.output.push_str("]");
 
@@ -119,88 +708,401 @@ impl BFLCompiler {
         self.move_to(dest);
     }
 
-    /// Peephole optimizer to remove redundant sequences
-    fn optimize_output(&mut self) {
-        let mut optimized = String::new();
-        let chars: Vec<char> = self.output.chars().collect();
-        let mut i = 0;
-        
-        while i < chars.len() {
-            // Remove redundant pointer movements: >< or <>
-            if i + 1 < chars.len() {
-                match (chars[i], chars[i + 1]) {
-                    ('>', '<') | ('<', '>') => {
-                        i += 2; // Skip both characters
-                        continue;
-                    }
-                    _ => {}
-                }
-            }
-            
-            // Remove redundant increments/decrements: +- or -+
-            if i + 1 < chars.len() {
-                match (chars[i], chars[i + 1]) {
-                    ('+', '-') | ('-', '+') => {
-                        i += 2; // Skip both characters
-                        continue;
-                    }
-                    _ => {}
-                }
-            }
-            
-            // Remove redundant loops: [][]
-            if i + 3 < chars.len() && chars[i] == '[' && chars[i + 1] == ']' && chars[i + 2] == '[' && chars[i + 3] == ']' {
-                i += 4; // Skip all four characters
-                continue;
-            }
-            
-            optimized.push(chars[i]);
-            i += 1;
+    /// Adds `src`'s value into `dest` without consuming `src` - `copy_value`
+    /// with an accumulating `dest` instead of a cleared one, the building
+    /// block `Mul`'s repeated-addition loop drives once per iteration.
+    /// `tmp` is disposable scratch, distinct from `src`/`dest`.
+    fn add_preserving(&mut self, src: usize, dest: usize, tmp: usize) {
+        self.move_to(tmp);
+        self.output.push_str("[-]");
+
+        self.move_to(src);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push('+');
+        self.move_to(tmp);
+        self.output.push('+');
+        self.move_to(src);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        self.move_to(tmp);
+        self.output.push_str("[");
+        self.move_to(src);
+        self.output.push('+');
+        self.move_to(tmp);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        self.move_to(dest);
+    }
+
+    /// Decrements `dest` by 1, but only if `dest` is still nonzero - the
+    /// same "guarded single decrement" building block `emit_wide_limb_add`
+    /// uses to turn an unbounded sum into a 0/1 carry flag. `guard` is
+    /// disposable scratch, distinct from `dest`.
+    fn guarded_decrement(&mut self, dest: usize, guard: usize) {
+        self.copy_value(dest, guard);
+        self.move_to(guard);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push('-');
+        self.move_to(guard);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.move_to(dest);
+    }
+
+    /// Computes `dest = max(dest - count_cell, 0)` via `count_cell` guarded
+    /// single-decrements of `dest` - true clamping, unlike a plain
+    /// `[dest-count-]` drain, which would wrap `dest` past zero once
+    /// `count_cell` outlives it, since these `u32` cells don't saturate on
+    /// their own. `count_cell` is drained to 0; `guard` is disposable
+    /// scratch.
+    fn clamped_subtract(&mut self, dest: usize, count_cell: usize, guard: usize) {
+        self.move_to(count_cell);
+        self.output.push_str("[");
+        self.guarded_decrement(dest, guard);
+        self.move_to(count_cell);
+        self.output.push('-');
+        self.output.push_str("]");
+    }
+
+    /// Computes `dest = max(a - b, 0)` from two cells, preserving both `a`
+    /// and `b` - the shared building block behind `Eq`/`Neq`/`Lt`/`Gt` (via
+    /// `compile_diffs`) and `compile_divmod`'s quotient/remainder loop.
+    /// `SCRATCH_7`/`SCRATCH_8` are used as disposable working cells.
+    fn emit_diff(&mut self, a: usize, b: usize, dest: usize) {
+        self.copy_value(a, dest);
+        self.copy_value(b, SCRATCH_7);
+        self.clamped_subtract(dest, SCRATCH_7, SCRATCH_8);
+    }
+
+    /// Sets `dest` to 1 if `src` is zero, 0 otherwise - the same
+    /// "seed a flag, clear it the moment a loop proves the condition false"
+    /// trick `compile_node`'s `If` uses, as a boolean NOT. `src` is drained
+    /// to 0; `flag` is disposable scratch.
+    fn emit_not_from_cell(&mut self, src: usize, flag: usize, dest: usize) {
+        self.move_to(flag);
+        self.output.push_str("[-]");
+        self.output.push('+');
+        self.move_to(src);
+        self.output.push_str("[");
+        self.move_to(flag);
+        self.output.push_str("[-]");
+        self.move_to(src);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.copy_value(flag, dest);
+    }
+
+    /// Sets `dest` to 1 if `src` is nonzero, 0 otherwise. `src` is drained
+    /// to 0; `tmp` is disposable scratch.
+    fn emit_bool_from_cell(&mut self, src: usize, tmp: usize, dest: usize) {
+        self.move_to(tmp);
+        self.output.push_str("[-]");
+        self.move_to(src);
+        self.output.push_str("[");
+        self.move_to(tmp);
+        self.output.push_str("[-]");
+        self.output.push('+');
+        self.move_to(src);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.copy_value(tmp, dest);
+    }
+
+    /// Sets `dest` to 1 if both `a` and `b` are nonzero, 0 otherwise -
+    /// logical AND of two flags. Both `a` and `b` are drained to 0.
+    fn emit_and_from_cells(&mut self, a: usize, b: usize, dest: usize) {
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.move_to(a);
+        self.output.push_str("[");
+        self.move_to(b);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.output.push('+');
+        self.move_to(b);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.move_to(a);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+    }
+
+    /// Sets `dest` to 1 if either `a` or `b` is nonzero, 0 otherwise -
+    /// logical OR of two flags. Both `a` and `b` are drained to 0.
+    fn emit_or_from_cells(&mut self, a: usize, b: usize, dest: usize) {
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.move_to(a);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.output.push('+');
+        self.move_to(a);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.move_to(b);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.output.push('+');
+        self.move_to(b);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+    }
+
+    /// Evaluates `lhs`/`rhs` and computes both clamped differences
+    /// (`max(lhs-rhs, 0)` and `max(rhs-lhs, 0)`) into `(SCRATCH_5,
+    /// SCRATCH_6)` - shared machinery for `Eq`/`Neq`/`Lt`/`Gt`, which all
+    /// reduce to comparing these two differences against zero. Exactly one
+    /// of the pair is nonzero unless the operands are equal, in which case
+    /// both are zero. `lhs` is held in a `held_operand_cell`, not the fixed
+    /// SCRATCH_3, so a compound `rhs` (e.g. a nested `Mul`) can't clobber it
+    /// while it's being evaluated - see `Mul`.
+    fn compile_diffs(&mut self, lhs: &BFLNode, rhs: &BFLNode) -> Result<(usize, usize), String> {
+        let held = self.held_operand_cell();
+        self.eval_to_cell(lhs, held)?;
+        self.scratch_depth += 1;
+        self.eval_to_cell(rhs, SCRATCH_4)?;
+        self.scratch_depth -= 1;
+        self.emit_diff(held, SCRATCH_4, SCRATCH_5);
+        self.emit_diff(SCRATCH_4, held, SCRATCH_6);
+        Ok((SCRATCH_5, SCRATCH_6))
+    }
+
+    /// Evaluates `lhs / rhs` (quotient) and `lhs % rhs` (remainder) into
+    /// `(SCRATCH_5, SCRATCH_6)` by repeated clamped subtraction: while the
+    /// remainder is still `>= divisor`, subtract the divisor once and add
+    /// one to the quotient - the textbook long-division-by-subtraction BF
+    /// idiom, structured as the same "evaluate condition, loop, re-evaluate
+    /// condition" shape `compile_node`'s `While` uses. Dividing by zero
+    /// never terminates, the same way dividing by zero in a native `/`
+    /// never returns - this doesn't special-case it. The divisor is held in
+    /// a `held_operand_cell`, not the fixed SCRATCH_4, so a compound `lhs`
+    /// (e.g. a nested `Div`) can't clobber it while it's being evaluated -
+    /// see `Mul`.
+    fn compile_divmod(&mut self, lhs: &BFLNode, rhs: &BFLNode) -> Result<(usize, usize), String> {
+        let divisor = self.held_operand_cell();
+        self.eval_to_cell(rhs, divisor)?; // divisor, kept intact for the whole loop
+        self.scratch_depth += 1;
+        self.eval_to_cell(lhs, SCRATCH_6)?; // remainder, updated in place
+        self.scratch_depth -= 1;
+        self.move_to(SCRATCH_5);
+        self.output.push_str("[-]"); // quotient := 0
+
+        // ge_flag (SCRATCH_8) := remainder >= divisor, i.e. max(divisor - remainder, 0) == 0
+        self.emit_diff(divisor, SCRATCH_6, SCRATCH_3);
+        self.emit_not_from_cell(SCRATCH_3, SCRATCH_7, SCRATCH_8);
+
+        self.move_to(SCRATCH_8);
+        self.output.push_str("[");
+
+        self.copy_value(divisor, SCRATCH_7); // count := divisor
+        self.clamped_subtract(SCRATCH_6, SCRATCH_7, SCRATCH_3); // remainder -= divisor
+        self.move_to(SCRATCH_5);
+        self.output.push('+'); // quotient += 1
+
+        self.emit_diff(divisor, SCRATCH_6, SCRATCH_3);
+        self.emit_not_from_cell(SCRATCH_3, SCRATCH_7, SCRATCH_8);
+        self.move_to(SCRATCH_8);
+        self.output.push_str("]");
+
+        Ok((SCRATCH_5, SCRATCH_6))
+    }
+
+    /// Returns the cell reserved for an internal (`__`-prefixed) variable
+    /// name used by node desugaring, bump-allocating it from
+    /// `next_var_location` on first use - the same mechanism `Assign` uses
+    /// for `Bytes`/`String` data - and reusing the same cell on every later
+    /// call so repeated desugarings (e.g. two `PrintNumber`s) don't leak a
+    /// fresh region each time.
+    fn reserve_internal_cell(&mut self, name: &str) -> usize {
+        if let Some(&cell) = self.variables.get(name) {
+            return cell;
         }
-        
-        self.output = optimized;
+        let cell = self.next_var_location;
+        self.next_var_location += 1;
+        self.variables.insert(name.to_string(), cell);
+        cell
+    }
+
+    /// Lowers `PrintNumber(expr)` into an equivalent `BFLNode::Block` built
+    /// from ordinary nodes, rather than emitting raw BF by hand. `u32` cells
+    /// hold at most 10 decimal digits, so this unrolls 10 divmod-by-10 steps
+    /// at Rust compile time, stashing each digit's ASCII value in its own
+    /// internal variable and counting how many digits actually came out;
+    /// printing then walks those 10 slots most-significant-first, skipping
+    /// the ones the counter says were never produced. `0` is special-cased
+    /// since the extraction loop would otherwise produce no digits at all.
+    ///
+    /// Each digit is printed with its own single-byte `write` syscall -
+    /// under `Mode::BFA` a bare `.` fires whatever syscall cells 1-7 are
+    /// primed for, so there's no such thing as a raw character print here.
+    /// `write`'s `PointerLen` argument is a guest cell *address*, not a
+    /// value (the same convention `Bytes` uses to hand a buffer to a
+    /// syscall), so a digit's ASCII value is first copied into a dedicated
+    /// one-cell `__print_number_outbuf` scratch buffer and that buffer's
+    /// address - known at desugar time - is passed as a `Number` literal,
+    /// rather than passing the digit variable itself (which would hand
+    /// `write` the digit's *value* as an address to read from).
+    fn desugar_print_number(&mut self, expr: &BFLNode) -> BFLNode {
+        const DIGITS: i32 = 10;
+        let n = "__print_number_n".to_string();
+        let count = "__print_number_count".to_string();
+        let outbuf = "__print_number_outbuf".to_string();
+        let digit_name = |i: i32| format!("__print_number_digit_{i}");
+
+        // Reserve all the internal variables up front, both so later cells
+        // (e.g. `outbuf`'s address) are known when building the statements
+        // below, and so `compile_node`'s `Assign` arm finds a cell already
+        // waiting for each of them - they're deliberately kept out of
+        // `allocate_cells`'s coloring pass since they're synthesized here,
+        // after that pass has already run.
+        self.reserve_internal_cell(&n);
+        self.reserve_internal_cell(&count);
+        for i in 0..DIGITS {
+            self.reserve_internal_cell(&digit_name(i));
+        }
+        let outbuf_addr = self.reserve_internal_cell(&outbuf);
+
+        let mut stmts = vec![
+            BFLNode::Assign(n.clone(), Box::new(expr.clone())),
+            BFLNode::Assign(count.clone(), Box::new(BFLNode::Number(0))),
+            BFLNode::If(
+                Box::new(BFLNode::Eq(
+                    Box::new(BFLNode::Variable(n.clone())),
+                    Box::new(BFLNode::Number(0)),
+                )),
+                vec![
+                    BFLNode::Assign(digit_name(0), Box::new(BFLNode::Number(b'0' as i32))),
+                    BFLNode::Assign(count.clone(), Box::new(BFLNode::Number(1))),
+                ],
+            ),
+        ];
+        for i in 0..DIGITS {
+            stmts.push(BFLNode::If(
+                Box::new(BFLNode::Variable(n.clone())),
+                vec![
+                    BFLNode::Assign(
+                        digit_name(i),
+                        Box::new(BFLNode::Add(
+                            Box::new(BFLNode::Mod(
+                                Box::new(BFLNode::Variable(n.clone())),
+                                Box::new(BFLNode::Number(10)),
+                            )),
+                            Box::new(BFLNode::Number(b'0' as i32)),
+                        )),
+                    ),
+                    BFLNode::Assign(
+                        n.clone(),
+                        Box::new(BFLNode::Div(
+                            Box::new(BFLNode::Variable(n.clone())),
+                            Box::new(BFLNode::Number(10)),
+                        )),
+                    ),
+                    BFLNode::Assign(
+                        count.clone(),
+                        Box::new(BFLNode::Add(
+                            Box::new(BFLNode::Variable(count.clone())),
+                            Box::new(BFLNode::Number(1)),
+                        )),
+                    ),
+                ],
+            ));
+        }
+        for p in (0..DIGITS).rev() {
+            stmts.push(BFLNode::If(
+                Box::new(BFLNode::Gt(
+                    Box::new(BFLNode::Variable(count.clone())),
+                    Box::new(BFLNode::Number(p)),
+                )),
+                vec![
+                    BFLNode::Assign(outbuf.clone(), Box::new(BFLNode::Variable(digit_name(p)))),
+                    BFLNode::Syscall(
+                        Box::new(BFLNode::String("write".to_string())),
+                        vec![
+                            BFLNode::Number(1),
+                            BFLNode::Number(outbuf_addr as i32),
+                            BFLNode::Number(1),
+                        ],
+                    ),
+                ],
+            ));
+        }
+
+        BFLNode::Block(stmts)
+    }
+
+    /// Lowers `Index(buffer, offset)` into a `peek` syscall over the computed
+    /// address - `buffer`'s value is already the base address a `Bytes`/
+    /// `String` assignment stored there, so adding `offset` to it and
+    /// handing that to `peek` is the same "value is an address" convention
+    /// `write` uses for buffer arguments, just without a real pointer
+    /// dereference (raw BF has none).
+    fn desugar_index(&self, buffer: &BFLNode, offset: &BFLNode) -> BFLNode {
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("peek".to_string())),
+            vec![BFLNode::Add(Box::new(buffer.clone()), Box::new(offset.clone()))],
+        )
+    }
+
+    /// Lowers `IndexAssign(buffer, offset, value)` into a `poke` syscall the
+    /// same way `desugar_index` lowers a read.
+    fn desugar_index_assign(&self, buffer: &BFLNode, offset: &BFLNode, value: &BFLNode) -> BFLNode {
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("poke".to_string())),
+            vec![
+                BFLNode::Add(Box::new(buffer.clone()), Box::new(offset.clone())),
+                value.clone(),
+            ],
+        )
+    }
+
+    /// Lowers `Poll(fds, nfds, timeout_ms)` into a `poll` syscall. The
+    /// syscall's `PointerLen` arg is declared in bytes, not struct count, so
+    /// `nfds` is scaled up by the 8-byte `struct pollfd` stride here rather
+    /// than asking callers to do the arithmetic themselves.
+    fn desugar_poll(&self, fds: &BFLNode, nfds: &BFLNode, timeout_ms: &BFLNode) -> BFLNode {
+        BFLNode::Syscall(
+            Box::new(BFLNode::String("poll".to_string())),
+            vec![
+                fds.clone(),
+                BFLNode::Mul(Box::new(nfds.clone()), Box::new(BFLNode::Number(8))),
+                timeout_ms.clone(),
+            ],
+        )
+    }
+
+    /// Lowers `IsError(expr)` into the `SYSCALL_ERROR_BASE` threshold check
+    /// described on the node itself - a plain `Gt`, cheap because both
+    /// operands stay in the small-number range this VM's arithmetic is
+    /// built for.
+    fn desugar_is_error(&self, expr: &BFLNode) -> BFLNode {
+        BFLNode::Gt(Box::new(expr.clone()), Box::new(BFLNode::Number(SYSCALL_ERROR_BASE - 1)))
+    }
+
+    /// Lowers `Errno(expr)` into the subtraction that undoes
+    /// `BF::encode_syscall_result`'s encoding.
+    fn desugar_errno(&self, expr: &BFLNode) -> BFLNode {
+        BFLNode::Sub(Box::new(expr.clone()), Box::new(BFLNode::Number(SYSCALL_ERROR_BASE)))
+    }
+
+    /// Peephole optimizer to remove redundant sequences. Delegates to the
+    /// `bfir` module's parse/optimize/serialize pipeline rather than
+    /// scanning characters directly - see that module's doc comment for why
+    /// it also catches zeroing and copy/multiply loops that a purely
+    /// adjacent-pair scan never could.
+    fn optimize_output(&mut self) {
+        self.output = crate::bfir::serialize(&crate::bfir::optimize(crate::bfir::parse(&self.output)));
     }
 
     /// Return an optimized version of the output without modifying internal state
     pub fn get_optimized_output_copy(&self) -> String {
-        let mut optimized = String::new();
-        let chars: Vec<char> = self.output.chars().collect();
-        let mut i = 0;
-        
-        while i < chars.len() {
-            // Remove redundant pointer movements: >< or <>
-            if i + 1 < chars.len() {
-                match (chars[i], chars[i + 1]) {
-                    ('>', '<') | ('<', '>') => {
-                        i += 2; // Skip both characters
-                        continue;
-                    }
-                    _ => {}
-                }
-            }
-            
-            // Remove redundant increments/decrements: +- or -+
-            if i + 1 < chars.len() {
-                match (chars[i], chars[i + 1]) {
-                    ('+', '-') | ('-', '+') => {
-                        i += 2; // Skip both characters
-                        continue;
-                    }
-                    _ => {}
-                }
-            }
-            
-            // Remove redundant loops: [][]
-            if i + 3 < chars.len() && chars[i] == '[' && chars[i + 1] == ']' && chars[i + 2] == '[' && chars[i + 3] == ']' {
-                i += 4; // Skip all four characters
-                continue;
-            }
-            
-            optimized.push(chars[i]);
-            i += 1;
-        }
-        
-        optimized
+        crate::bfir::serialize(&crate::bfir::optimize(crate::bfir::parse(&self.output)))
     }
 
     /// Evaluate an expression, storing its final value in the specified cell.
@@ -230,6 +1132,7 @@ impl BFLCompiler {
             BFLNode::Bytes(bytes) => {
                 let data_location = self.next_var_location;
                 self.next_var_location += bytes.len();
+                self.regions.insert(data_location, bytes.len());
 
                 // Store pointer to data in the dest cell
                 self.move_to(dest);
@@ -272,58 +1175,468 @@ impl BFLCompiler {
                 self.output.push_str("]");
                 self.move_to(dest);
             }
+            BFLNode::Mul(lhs, rhs) => {
+                // Multiply by repeated addition: VAL added to the product
+                // once per COUNTER iteration, via the non-destructive
+                // `add_preserving` so VAL survives the whole loop. VAL is
+                // kept in a `held_operand_cell` rather than the fixed
+                // SCRATCH_3, since COUNTER's evaluation (which can itself be
+                // a nested `Mul`/`Eq`/etc. that uses its own SCRATCH_3) must
+                // not be able to clobber it. The product accumulates in
+                // SCRATCH_5, not `dest` directly, and is only copied over at
+                // the end - the same way `compile_divmod` keeps its own
+                // accumulator off of `dest` - so this still works when
+                // `dest` happens to be one of this op's own scratch cells
+                // (e.g. a `Mul` nested as another op's RHS operand, which
+                // lands in SCRATCH_1).
+                let val = self.held_operand_cell();
+                self.eval_to_cell(lhs, val)?; // VAL
+                self.scratch_depth += 1;
+                self.eval_to_cell(rhs, SCRATCH_4)?; // COUNTER
+                self.scratch_depth -= 1;
+                self.move_to(SCRATCH_5);
+                self.output.push_str("[-]"); // product accumulates here
+                self.move_to(SCRATCH_4);
+                self.output.push_str("[");
+                self.add_preserving(val, SCRATCH_5, SCRATCH_1);
+                self.move_to(SCRATCH_4);
+                self.output.push('-');
+                self.output.push_str("]");
+                self.copy_value(SCRATCH_5, dest);
+            }
+            BFLNode::Div(lhs, rhs) => {
+                let (quotient, _remainder) = self.compile_divmod(lhs, rhs)?;
+                self.copy_value(quotient, dest);
+            }
+            BFLNode::Mod(lhs, rhs) => {
+                let (_quotient, remainder) = self.compile_divmod(lhs, rhs)?;
+                self.copy_value(remainder, dest);
+            }
+            BFLNode::Eq(lhs, rhs) => {
+                let (diff_ab, diff_ba) = self.compile_diffs(lhs, rhs)?;
+                self.add_preserving(diff_ba, diff_ab, SCRATCH_7);
+                self.emit_not_from_cell(diff_ab, SCRATCH_7, dest);
+            }
+            BFLNode::Neq(lhs, rhs) => {
+                let (diff_ab, diff_ba) = self.compile_diffs(lhs, rhs)?;
+                self.add_preserving(diff_ba, diff_ab, SCRATCH_7);
+                self.emit_bool_from_cell(diff_ab, SCRATCH_7, dest);
+            }
+            BFLNode::Lt(lhs, rhs) => {
+                // lhs < rhs iff diff_ab == 0 (lhs <= rhs) and diff_ba != 0 (not equal).
+                let (diff_ab, diff_ba) = self.compile_diffs(lhs, rhs)?;
+                self.emit_not_from_cell(diff_ab, SCRATCH_7, SCRATCH_3);
+                self.emit_bool_from_cell(diff_ba, SCRATCH_7, SCRATCH_4);
+                self.emit_and_from_cells(SCRATCH_3, SCRATCH_4, dest);
+            }
+            BFLNode::Gt(lhs, rhs) => {
+                // lhs > rhs iff diff_ab != 0 (not equal) and diff_ba == 0 (rhs <= lhs).
+                let (diff_ab, diff_ba) = self.compile_diffs(lhs, rhs)?;
+                self.emit_bool_from_cell(diff_ab, SCRATCH_7, SCRATCH_3);
+                self.emit_not_from_cell(diff_ba, SCRATCH_7, SCRATCH_4);
+                self.emit_and_from_cells(SCRATCH_3, SCRATCH_4, dest);
+            }
+            BFLNode::And(lhs, rhs) => {
+                // `lhs` is held in a `held_operand_cell`, not the fixed
+                // SCRATCH_3, so a compound `rhs` (e.g. a nested `Eq`) can't
+                // clobber it while it's being evaluated - see `Mul`.
+                let held = self.held_operand_cell();
+                self.eval_to_cell(lhs, held)?;
+                self.scratch_depth += 1;
+                self.eval_to_cell(rhs, SCRATCH_4)?;
+                self.scratch_depth -= 1;
+                self.emit_bool_from_cell(held, SCRATCH_7, SCRATCH_5);
+                self.emit_bool_from_cell(SCRATCH_4, SCRATCH_7, SCRATCH_6);
+                self.emit_and_from_cells(SCRATCH_5, SCRATCH_6, dest);
+            }
+            BFLNode::Or(lhs, rhs) => {
+                // See `And` above for why `lhs` is held in a
+                // `held_operand_cell` instead of the fixed SCRATCH_3.
+                let held = self.held_operand_cell();
+                self.eval_to_cell(lhs, held)?;
+                self.scratch_depth += 1;
+                self.eval_to_cell(rhs, SCRATCH_4)?;
+                self.scratch_depth -= 1;
+                self.emit_bool_from_cell(held, SCRATCH_7, SCRATCH_5);
+                self.emit_bool_from_cell(SCRATCH_4, SCRATCH_7, SCRATCH_6);
+                self.emit_or_from_cells(SCRATCH_5, SCRATCH_6, dest);
+            }
+            BFLNode::Not(operand) => {
+                self.eval_to_cell(operand, SCRATCH_3)?;
+                self.emit_not_from_cell(SCRATCH_3, SCRATCH_7, dest);
+            }
+            BFLNode::Index(buffer, offset) => {
+                let peek = self.desugar_index(buffer, offset);
+                self.compile_node(&peek)?;
+                self.copy_value(0, dest); // peek's result lands in _syscall_result (cell 0)
+            }
+            BFLNode::IsError(inner) => {
+                let is_error = self.desugar_is_error(inner);
+                self.eval_to_cell(&is_error, dest)?;
+            }
+            BFLNode::Errno(inner) => {
+                let errno = self.desugar_errno(inner);
+                self.eval_to_cell(&errno, dest)?;
+            }
             _ => return Err(format!("Cannot evaluate this node type directly: {:?}", expr)),
         }
         Ok(())
     }
 
+    /// Writes `value`'s little-endian base-256 digits into `width` cells
+    /// starting at `dest` - `WideNumber`'s codegen, and `Number`'s
+    /// multi-cell counterpart.
+    fn emit_wide_number(&mut self, mut value: u64, dest: usize, width: usize) {
+        for i in 0..width {
+            let digit = (value & 0xFF) as usize;
+            value >>= 8;
+            self.move_to(dest + i);
+            self.output.push_str("[-]");
+            if digit > 0 {
+                self.output.push_str(&"+".repeat(digit));
+            }
+        }
+    }
+
+    /// Replaces `cell`'s value with its 255's complement (`255 - cell`),
+    /// using `tmp` as disposable scratch. Safe without a guard since a wide
+    /// limb is always 0-255, so `255 - cell` never underflows.
+    fn complement_byte(&mut self, cell: usize, tmp: usize) {
+        self.copy_value(cell, tmp);
+        self.move_to(cell);
+        self.output.push_str("[-]");
+        self.output.push_str(&"+".repeat(255));
+        self.move_to(tmp);
+        self.output.push_str("[");
+        self.move_to(cell);
+        self.output.push('-');
+        self.move_to(tmp);
+        self.output.push('-');
+        self.output.push_str("]");
+        self.move_to(cell);
+    }
+
+    /// Adds `rhs_limb` and the running `scratch.carry` into `dest` in place
+    /// and writes the new carry-out back into `scratch.carry`. The rest of
+    /// `scratch` is disposable, clobbered by this call.
+    ///
+    /// `dest + rhs_limb + carry` can reach 510, past a limb's 0-255 range,
+    /// and these cells don't wrap at 256 on their own (see
+    /// `BFLNode::WideNumber`), so the carry has to be computed explicitly:
+    /// a `bound` cell counts down from 256, and each of its 256 loop
+    /// iterations does one guarded single decrement of `tmp` - "guarded"
+    /// meaning it only fires if `tmp` is still nonzero, using the same
+    /// self-clearing-flag idiom `If`'s `cond_loc` uses to run its body
+    /// exactly once. After 256 iterations, `counter` (which that guarded
+    /// decrement increments each time it fires) holds `min(sum, 256)` and
+    /// `tmp` holds `max(sum - 256, 0)`. A second, 255-iteration pass of the
+    /// same trick collapses `counter` from `{0..=255, 256}` down to the
+    /// `{0, 1}` carry flag. Finally `dest` is overwritten with `tmp` only
+    /// when that flag is set - when it isn't, `dest` already holds the
+    /// right mod-256 result untouched.
+    fn emit_wide_limb_add(&mut self, dest: usize, rhs_limb: usize, scratch: WideScratch) {
+        let WideScratch { carry, tmp, counter, bound, flag } = scratch;
+
+        // dest += rhs_limb (destructive drain of the scratch limb)
+        self.move_to(rhs_limb);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push('+');
+        self.move_to(rhs_limb);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        // dest += carry (destructive drain of the carry-in)
+        self.move_to(carry);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push('+');
+        self.move_to(carry);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        // tmp = a copy of the (possibly >255) sum now sitting in dest
+        self.copy_value(dest, tmp);
+
+        // counter = min(tmp, 256): 256 guarded single-decrements of tmp.
+        self.move_to(counter);
+        self.output.push_str("[-]");
+        self.move_to(bound);
+        self.output.push_str("[-]");
+        self.output.push_str(&"+".repeat(256));
+        self.move_to(bound);
+        self.output.push_str("[");
+        self.copy_value(tmp, flag);
+        self.move_to(flag);
+        self.output.push_str("[");
+        self.move_to(tmp);
+        self.output.push('-');
+        self.move_to(counter);
+        self.output.push('+');
+        self.move_to(flag);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.move_to(bound);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        // counter -= 255, clamped at 0: collapses it down to the {0, 1}
+        // carry flag, via the same guarded-decrement loop run 255 times.
+        self.move_to(bound);
+        self.output.push_str("[-]");
+        self.output.push_str(&"+".repeat(255));
+        self.move_to(bound);
+        self.output.push_str("[");
+        self.copy_value(counter, flag);
+        self.move_to(flag);
+        self.output.push_str("[");
+        self.move_to(counter);
+        self.output.push('-');
+        self.move_to(flag);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+        self.move_to(bound);
+        self.output.push('-');
+        self.output.push_str("]");
+
+        // dest already holds sum mod 256 unless the carry fired, in which
+        // case it needs overwriting with tmp (= sum - 256).
+        self.copy_value(counter, flag);
+        self.move_to(flag);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push_str("[-]");
+        self.move_to(tmp);
+        self.output.push_str("[");
+        self.move_to(dest);
+        self.output.push('+');
+        self.move_to(tmp);
+        self.output.push('-');
+        self.output.push_str("]");
+        self.move_to(flag);
+        self.output.push_str("[-]");
+        self.output.push_str("]");
+
+        // carry <- counter (the 0/1 carry-out); counter is now spent.
+        self.copy_value(counter, carry);
+        self.move_to(counter);
+        self.output.push_str("[-]");
+    }
+
+    /// Evaluates a wide (`width`-limb) expression into the `width` cells
+    /// starting at `dest` - the multi-cell counterpart to `eval_to_cell`.
+    fn eval_wide_to_cells(&mut self, expr: &BFLNode, dest: usize, width: usize) -> Result<(), String> {
+        match expr {
+            BFLNode::WideNumber(value, _) => {
+                self.emit_wide_number(*value, dest, width);
+            }
+            BFLNode::Variable(name) => {
+                let src = *self.variables.get(name).ok_or(format!("Variable '{}' not found", name))?;
+                for i in 0..width {
+                    self.copy_value(src + i, dest + i);
+                }
+            }
+            BFLNode::WideAdd(lhs, rhs, w) => self.compile_wide_merge(lhs, rhs, *w, dest, false)?,
+            BFLNode::WideSub(lhs, rhs, w) => self.compile_wide_merge(lhs, rhs, *w, dest, true)?,
+            _ => return Err(format!("Cannot evaluate this node as a wide integer: {:?}", expr)),
+        }
+        Ok(())
+    }
+
+    /// Evaluates `lhs` into `dest`'s `width` limbs, evaluates `rhs` into the
+    /// `WIDE_SCRATCH_BASE` scratch region, then adds them limb by limb with
+    /// carry propagation (see `emit_wide_limb_add`). For subtraction, `rhs`'s
+    /// limbs are first replaced with their 255's complement and the initial
+    /// carry seeded to 1 - the standard complement-plus-one trick, so the
+    /// same per-limb adder serves both operations. A carry out of the final
+    /// (most significant) limb is simply discarded, so the result wraps
+    /// modulo `256^width`.
+    fn compile_wide_merge(
+        &mut self,
+        lhs: &BFLNode,
+        rhs: &BFLNode,
+        width: usize,
+        dest: usize,
+        is_sub: bool,
+    ) -> Result<(), String> {
+        self.eval_wide_to_cells(lhs, dest, width)?;
+        let rhs_base = WIDE_SCRATCH_BASE;
+        self.eval_wide_to_cells(rhs, rhs_base, width)?;
+
+        let scratch = WideScratch {
+            carry: rhs_base + width,
+            tmp: rhs_base + width + 1,
+            counter: rhs_base + width + 2,
+            bound: rhs_base + width + 3,
+            flag: rhs_base + width + 4,
+        };
+
+        self.move_to(scratch.carry);
+        self.output.push_str("[-]");
+
+        if is_sub {
+            for i in 0..width {
+                self.complement_byte(rhs_base + i, scratch.tmp);
+            }
+            self.move_to(scratch.carry);
+            self.output.push('+');
+        }
+
+        for i in 0..width {
+            self.emit_wide_limb_add(dest + i, rhs_base + i, scratch);
+        }
+        Ok(())
+    }
+
+    /// Register-allocates every variable `node` assigns, then emits its code.
+    /// Before codegen, `allocate_cells` walks the whole tree once up front so
+    /// every `Assign` a recursive `compile_node` call encounters already has
+    /// a cell reserved for it.
     pub fn compile(&mut self, node: &BFLNode) -> Result<(), String> {
+        self.allocate_cells(node);
+        self.compile_node(node)
+    }
+
+    /// Maps every variable `node` assigns to a concrete cell, reusing cells
+    /// between variables whose live ranges never overlap instead of bumping
+    /// `next_var_location` forever. Builds each variable's live range (see
+    /// `live_ranges`), an interference graph with an edge between any two
+    /// overlapping ranges, greedily colors it (lowest free color, in
+    /// descending-degree order so the most-constrained variables pick first),
+    /// then maps color `c` to cell `BASE_VAR_CELL + c`. `_syscall_result` is
+    /// pinned to cell 0 from `new` and is never recolored. `Bytes`/`String`
+    /// data is never colored either - it keeps bumping forward from above the
+    /// colored region (`next_var_location`), so a pointer's backing data is
+    /// never reused for anything else.
+    fn allocate_cells(&mut self, node: &BFLNode) {
+        let ranges = live_ranges(node);
+        let wide_names = wide_variable_names(node);
+        let mut names: Vec<String> = ranges
+            .keys()
+            .filter(|name| name.as_str() != "_syscall_result" && !wide_names.contains(name.as_str()))
+            .cloned()
+            .collect();
+        names.sort();
+
+        let mut graph = Graph::new();
+        for i in 0..names.len() {
+            graph.add_node(i);
+        }
+        for i in 0..names.len() {
+            for j in (i + 1)..names.len() {
+                let (a_first, a_last) = ranges[&names[i]];
+                let (b_first, b_last) = ranges[&names[j]];
+                if a_first <= b_last && b_first <= a_last {
+                    graph.add_edge(i, j);
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..names.len()).collect();
+        order.sort_by_key(|&n| core::cmp::Reverse(graph.degree(n)));
+
+        let mut colors: HashMap<usize, usize> = HashMap::new();
+        for &n in &order {
+            let used: HashSet<usize> =
+                graph.neighbors(n).filter_map(|nb| colors.get(nb).copied()).collect();
+            let mut color = 0;
+            while used.contains(&color) {
+                color += 1;
+            }
+            colors.insert(n, color);
+        }
+
+        let num_colors = colors.values().copied().max().map_or(0, |m| m + 1);
+        for (i, name) in names.iter().enumerate() {
+            self.variables.insert(name.clone(), BASE_VAR_CELL + colors[&i]);
+        }
+        self.next_var_location = BASE_VAR_CELL + num_colors;
+    }
+
+    fn compile_node(&mut self, node: &BFLNode) -> Result<(), String> {
         match node {
             BFLNode::Block(statements) => {
                 for stmt in statements {
-                    self.compile(stmt)?;
+                    self.compile_node(stmt)?;
                 }
             }
             BFLNode::Assign(name, expr) => {
-                let location = *self.variables.entry(name.clone()).or_insert_with(|| {
-                    let loc = self.next_var_location;
-                    self.next_var_location += 1;
-                    loc
-                });
-                self.eval_to_cell(expr, location)?;
+                let start = self.output.len();
+                let description = if let Some(width) = wide_width(expr) {
+                    // `allocate_cells` kept this name out of the colored
+                    // region entirely, so bump-allocate its `width` cells
+                    // now, the same way a `Bytes`/`String` region is - only
+                    // directly, with no pointer indirection.
+                    let base = self.next_var_location;
+                    self.next_var_location += width;
+                    self.variables.insert(name.clone(), base);
+                    self.eval_wide_to_cells(expr, base, width)?;
+                    format!("Assign {name} -> cells {base}..{}", base + width)
+                } else {
+                    let location = *self
+                        .variables
+                        .get(name)
+                        .ok_or_else(|| format!("Variable '{}' was not allocated a cell", name))?;
+                    self.eval_to_cell(expr, location)?;
+                    format!("Assign {name} -> cell {location}")
+                };
+                self.record_span(start, description);
             }
             BFLNode::While(condition, body) => {
+                let start = self.output.len();
                 let cond_loc = SCRATCH_2;
                 self.eval_to_cell(condition, cond_loc)?; // Initial condition check
                 self.move_to(cond_loc);
                 self.output.push('['); // Loop while condition is non-zero
-                
+
+                self.loop_depth += 1;
                 for stmt in body {
-                    self.compile(stmt)?;
+                    self.compile_node(stmt)?;
                 }
-                
+                self.loop_depth -= 1;
+
                 self.eval_to_cell(condition, cond_loc)?; // Re-evaluate condition at the end of the loop
                 self.move_to(cond_loc);
                 self.output.push(']');
+                self.record_span(start, format!("While cond @ cell {cond_loc}"));
             }
             BFLNode::If(condition, body) => {
+                let start = self.output.len();
                 let cond_loc = SCRATCH_2;
                 self.eval_to_cell(condition, cond_loc)?;
                 self.move_to(cond_loc);
                 self.output.push('['); // If condition is non-zero
-                
+
+                self.loop_depth += 1;
                 for stmt in body {
-                    self.compile(stmt)?;
+                    self.compile_node(stmt)?;
                 }
-                
+                self.loop_depth -= 1;
+
                 // Clear the flag to ensure the 'if' block runs only once
                 self.move_to(cond_loc);
                 self.output.push_str("[-]");
                 self.output.push(']');
+                self.record_span(start, format!("If cond @ cell {cond_loc}"));
             }
             BFLNode::Syscall(syscall_no, args) => {
-                // Evaluate syscall number into cell 7
-                self.eval_to_cell(syscall_no, 7)?;
+                let start = self.output.len();
+                // Evaluate syscall number into cell 7, resolving a named
+                // syscall (e.g. "write") to its number first.
+                let resolved = match syscall_no.as_ref() {
+                    BFLNode::String(name) => {
+                        let num = *self
+                            .syscall_names
+                            .get(name)
+                            .ok_or_else(|| format!("Unknown syscall name: '{}'", name))?;
+                        self.eval_to_cell(&BFLNode::Number(num as i32), 7)?;
+                        format!("\"{name}\" (#{num})")
+                    }
+                    _ => {
+                        self.eval_to_cell(syscall_no, 7)?;
+                        "#?".to_string()
+                    }
+                };
 
                 // Evaluate arguments into cells 1-6
                 for (i, arg) in args.iter().enumerate() {
@@ -336,6 +1649,25 @@ impl BFLCompiler {
 
                 // Execute syscall
                 self.output.push('.');
+                self.record_span(start, format!("Syscall {resolved}"));
+            }
+            BFLNode::PrintNumber(expr) => {
+                let start = self.output.len();
+                let desugared = self.desugar_print_number(expr);
+                self.compile_node(&desugared)?;
+                self.record_span(start, "PrintNumber".to_string());
+            }
+            BFLNode::IndexAssign(buffer, offset, value) => {
+                let start = self.output.len();
+                let poke = self.desugar_index_assign(buffer, offset, value);
+                self.compile_node(&poke)?;
+                self.record_span(start, "IndexAssign".to_string());
+            }
+            BFLNode::Poll(fds, nfds, timeout_ms) => {
+                let start = self.output.len();
+                let poll = self.desugar_poll(fds, nfds, timeout_ms);
+                self.compile_node(&poll)?;
+                self.record_span(start, "Poll".to_string());
             }
             // Expressions are handled by `eval_to_cell` and shouldn't be top-level statements
             _ => return Err(format!("Node type {:?} cannot be a top-level statement", node)),
@@ -355,4 +1687,200 @@ impl BFLCompiler {
     pub fn get_variable_address(&self, name: &str) -> Option<usize> {
         self.variables.get(name).copied()
     }
+
+    /// `start_addr -> byte_len` for every `Bytes`/`String` allocation this
+    /// program made, for `BF::with_memory_regions`.
+    pub fn get_regions(&self) -> HashMap<usize, usize> {
+        self.regions.clone()
+    }
+
+    /// Records a disassembly span covering everything `compile_node` just
+    /// emitted, from `start` up to the output's current length, at the
+    /// current loop nesting depth.
+    fn record_span(&mut self, start: usize, description: String) {
+        self.spans.push(DisasmItem {
+            start,
+            end: self.output.len(),
+            description,
+            depth: self.loop_depth,
+        });
+    }
+
+    /// The disassembly side table `compile`/`compile_node` built up, one
+    /// item per top-level statement, in source order.
+    pub fn disasm(&self) -> Vec<DisasmItem> {
+        let mut spans = self.spans.clone();
+        spans.sort_by_key(|span| span.start);
+        spans
+    }
+
+    /// Renders the compiled BF interleaved with `;` comments describing
+    /// which `BFLNode` produced each stretch, indented by loop nesting
+    /// depth - not valid BF itself, just a debugging view.
+    pub fn render_annotated(&self) -> String {
+        let mut rendered = String::new();
+        for span in self.disasm() {
+            let indent = "  ".repeat(span.depth);
+            rendered.push_str(&indent);
+            rendered.push_str("; ");
+            rendered.push_str(&span.description);
+            rendered.push('\n');
+            rendered.push_str(&indent);
+            rendered.push_str(&self.output[span.start..span.end]);
+            rendered.push('\n');
+        }
+        rendered
+    }
+
+    /// Finds the innermost recorded span covering byte `offset` of
+    /// `get_output`'s string - the reverse of `disasm`, for "what BFL node
+    /// produced the code at this position" debugging.
+    pub fn node_at_offset(&self, offset: usize) -> Result<DisasmItem, DisasmError> {
+        if offset >= self.output.len() {
+            return Err(DisasmError::OffsetOutOfRange { offset, len: self.output.len() });
+        }
+        self.spans
+            .iter()
+            .filter(|span| span.start <= offset && offset < span.end)
+            .min_by_key(|span| span.end - span.start)
+            .cloned()
+            .ok_or(DisasmError::NoSpanFound { offset })
+    }
+
+    /// Walks `node` before codegen, collecting every semantic error instead
+    /// of stopping at the first one (or silently miscompiling, as `compile`
+    /// would): a syscall length that overruns its buffer variable's declared
+    /// size, `Add`/`Sub` mixing a buffer with a number, and references to
+    /// variables nothing ever assigned. Doesn't touch `self`'s own state, so
+    /// it can run before (and regardless of) `compile`.
+    pub fn analyze(&self, node: &BFLNode) -> Result<(), Vec<BflError>> {
+        let mut ctx = AnalysisCtx {
+            assigned: self.variables.keys().cloned().collect(),
+            buffer_lens: HashMap::new(),
+            next_index: 0,
+            errors: Vec::new(),
+        };
+        self.analyze_stmt(node, &mut ctx);
+        if ctx.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ctx.errors)
+        }
+    }
+
+    fn analyze_stmt(&self, node: &BFLNode, ctx: &mut AnalysisCtx) {
+        match node {
+            BFLNode::Block(stmts) => {
+                for stmt in stmts {
+                    self.analyze_stmt(stmt, ctx);
+                }
+            }
+            BFLNode::Assign(name, expr) => {
+                self.analyze_expr(expr, ctx);
+                match expr.as_ref() {
+                    BFLNode::Bytes(bytes) => {
+                        ctx.buffer_lens.insert(name.clone(), bytes.len());
+                    }
+                    BFLNode::String(s) => {
+                        ctx.buffer_lens.insert(name.clone(), s.len());
+                    }
+                    _ => {}
+                }
+                ctx.assigned.insert(name.clone());
+            }
+            BFLNode::If(cond, body) | BFLNode::While(cond, body) => {
+                self.analyze_expr(cond, ctx);
+                for stmt in body {
+                    self.analyze_stmt(stmt, ctx);
+                }
+            }
+            BFLNode::Syscall(syscall_no, args) => {
+                // A named syscall resolves against `syscall_names`, not a
+                // variable - don't check it as an expression.
+                if !matches!(syscall_no.as_ref(), BFLNode::String(_)) {
+                    self.analyze_expr(syscall_no, ctx);
+                }
+                for i in 0..args.len() {
+                    self.analyze_expr(&args[i], ctx);
+                    if let BFLNode::Variable(name) = &args[i] {
+                        if let Some(&declared) = ctx.buffer_lens.get(name) {
+                            if let Some(BFLNode::Number(used)) = args.get(i + 1) {
+                                let used = *used as usize;
+                                if used > declared {
+                                    let span = ctx.span();
+                                    ctx.errors.push(BflError::BufferLengthMismatch {
+                                        span,
+                                        declared,
+                                        used,
+                                    });
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            BFLNode::PrintNumber(expr) => self.analyze_expr(expr, ctx),
+            BFLNode::IndexAssign(buffer, offset, value) => {
+                self.analyze_expr(buffer, ctx);
+                self.analyze_expr(offset, ctx);
+                self.analyze_expr(value, ctx);
+            }
+            BFLNode::Poll(fds, nfds, timeout_ms) => {
+                self.analyze_expr(fds, ctx);
+                self.analyze_expr(nfds, ctx);
+                self.analyze_expr(timeout_ms, ctx);
+            }
+            _ => self.analyze_expr(node, ctx),
+        }
+    }
+
+    fn analyze_expr(&self, node: &BFLNode, ctx: &mut AnalysisCtx) {
+        match node {
+            BFLNode::Variable(name) => {
+                if !ctx.assigned.contains(name) {
+                    let span = ctx.span();
+                    ctx.errors.push(BflError::UndefinedVariable { span, name: name.clone() });
+                }
+            }
+            BFLNode::Add(lhs, rhs) | BFLNode::Sub(lhs, rhs) => {
+                self.analyze_expr(lhs, ctx);
+                self.analyze_expr(rhs, ctx);
+                if let (Some(l), Some(r)) =
+                    (infer_kind(lhs, &ctx.buffer_lens), infer_kind(rhs, &ctx.buffer_lens))
+                {
+                    if l != r {
+                        let (expected, found) = match l {
+                            ValueKind::Number => ("Number", "Buffer"),
+                            ValueKind::Buffer => ("Buffer", "Number"),
+                        };
+                        let span = ctx.span();
+                        ctx.errors.push(BflError::TypeMismatch { span, expected, found });
+                    }
+                }
+            }
+            BFLNode::WideAdd(lhs, rhs, _) | BFLNode::WideSub(lhs, rhs, _) => {
+                self.analyze_expr(lhs, ctx);
+                self.analyze_expr(rhs, ctx);
+            }
+            BFLNode::Mul(lhs, rhs)
+            | BFLNode::Div(lhs, rhs)
+            | BFLNode::Mod(lhs, rhs)
+            | BFLNode::Eq(lhs, rhs)
+            | BFLNode::Neq(lhs, rhs)
+            | BFLNode::Lt(lhs, rhs)
+            | BFLNode::Gt(lhs, rhs)
+            | BFLNode::And(lhs, rhs)
+            | BFLNode::Or(lhs, rhs) => {
+                self.analyze_expr(lhs, ctx);
+                self.analyze_expr(rhs, ctx);
+            }
+            BFLNode::Not(operand) => self.analyze_expr(operand, ctx),
+            BFLNode::Index(buffer, offset) => {
+                self.analyze_expr(buffer, ctx);
+                self.analyze_expr(offset, ctx);
+            }
+            BFLNode::IsError(expr) | BFLNode::Errno(expr) => self.analyze_expr(expr, ctx),
+            _ => {}
+        }
+    }
 }
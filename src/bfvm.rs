@@ -4,11 +4,15 @@ use crate::{
 };
 use anyhow::Context;
 use nix::{
-    sys::socket::{self, AddressFamily, SockFlag, SockType, SockaddrIn},
+    sys::socket::{self, sockopt, AddressFamily, SockFlag, SockType, SockaddrIn},
+    sys::uio::{readv, writev},
     unistd,
 };
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 use std::{
-    io::Read,
+    collections::HashMap,
+    io::{IoSlice, IoSliceMut, Read},
     net::Ipv4Addr,
     os::fd::{AsFd, OwnedFd},
     os::unix::io::{AsRawFd, FromRawFd},
@@ -20,12 +24,126 @@ const SYSCALL_RESULT_OFFSET: usize = 0;
 const SYSCALL_NUMBER_OFFSET: usize = 4;
 const SYSCALL_ARGS_OFFSET: usize = 8;
 
+// Subset of Linux's <sys/socket.h> constants needed by `Setsockopt`.
+const SOL_SOCKET: u32 = 1;
+const SO_REUSEADDR: u32 = 2;
+const SO_REUSEPORT: u32 = 15;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BFMode {
     Normal,
     Syscall,
 }
 
+/// Per-operation-class compute unit costs, modeled on the Solana BPF loader's
+/// `ComputeMeter`. Pointer/value ops are cheap, I/O is pricier, and each
+/// syscall has its own tunable cost so expensive operations (e.g. opening a
+/// socket) can't be disguised as a handful of cheap cell increments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostTable {
+    pub pointer_op: u64,
+    pub value_op: u64,
+    pub io_op: u64,
+    pub syscall_read: u64,
+    pub syscall_write: u64,
+    pub syscall_socket: u64,
+    pub syscall_bind: u64,
+    pub syscall_listen: u64,
+    pub syscall_accept: u64,
+    pub syscall_close: u64,
+    pub syscall_get_compute_units: u64,
+    pub syscall_sha256: u64,
+    pub syscall_keccak256: u64,
+    pub syscall_blake3: u64,
+    pub syscall_readv: u64,
+    pub syscall_writev: u64,
+    pub syscall_brk: u64,
+    /// Charged for any syscall number handled by a user-registered
+    /// `SyscallHandler` rather than the built-in POSIX set.
+    pub syscall_custom_default: u64,
+    pub syscall_setsockopt: u64,
+    pub syscall_accept4: u64,
+    pub syscall_sendto: u64,
+    pub syscall_recvfrom: u64,
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self {
+            pointer_op: 1,
+            value_op: 1,
+            io_op: 10,
+            syscall_read: 100,
+            syscall_write: 100,
+            syscall_socket: 500,
+            syscall_bind: 200,
+            syscall_listen: 200,
+            syscall_accept: 1_000,
+            syscall_close: 50,
+            syscall_get_compute_units: 1,
+            syscall_sha256: 300,
+            syscall_keccak256: 300,
+            syscall_blake3: 150,
+            syscall_readv: 120,
+            syscall_writev: 120,
+            syscall_brk: 50,
+            syscall_custom_default: 100,
+            syscall_setsockopt: 100,
+            syscall_accept4: 1_000,
+            syscall_sendto: 100,
+            syscall_recvfrom: 100,
+        }
+    }
+}
+
+impl CostTable {
+    fn syscall_cost(&self, syscall: SyscallNum) -> u64 {
+        match syscall {
+            SyscallNum::Read => self.syscall_read,
+            SyscallNum::Write => self.syscall_write,
+            SyscallNum::Socket => self.syscall_socket,
+            SyscallNum::Bind => self.syscall_bind,
+            SyscallNum::Listen => self.syscall_listen,
+            SyscallNum::Accept => self.syscall_accept,
+            SyscallNum::Close => self.syscall_close,
+            SyscallNum::GetComputeUnits => self.syscall_get_compute_units,
+            SyscallNum::Sha256 => self.syscall_sha256,
+            SyscallNum::Keccak256 => self.syscall_keccak256,
+            SyscallNum::Blake3 => self.syscall_blake3,
+            SyscallNum::Readv => self.syscall_readv,
+            SyscallNum::Writev => self.syscall_writev,
+            SyscallNum::Brk => self.syscall_brk,
+            SyscallNum::Setsockopt => self.syscall_setsockopt,
+            SyscallNum::Accept4 => self.syscall_accept4,
+            SyscallNum::Sendto => self.syscall_sendto,
+            SyscallNum::Recvfrom => self.syscall_recvfrom,
+        }
+    }
+}
+
+/// A host-provided handler for a custom syscall number. Receives read/write
+/// access to the VM through its public accessor methods (`arg`, `memory`,
+/// `memory_mut`, `set_syscall_result`) rather than raw pointers, so custom
+/// syscalls stay bounds-checked the same way the built-ins are.
+pub trait SyscallHandler {
+    fn invoke(&mut self, vm: &mut BFVM, args: &[u32; 6]) -> VMResult<u32>;
+}
+
+/// Maps syscall numbers to host-provided handlers. Numbers outside this map
+/// fall back to the built-in POSIX set (`Read`, `Write`, `Socket`, ...), so
+/// embedders can add domain-specific syscalls (timers, RNG, a KV store)
+/// without forking the VM.
+#[derive(Default)]
+pub struct SyscallRegistry {
+    handlers: HashMap<u32, Box<dyn SyscallHandler>>,
+}
+
+impl SyscallRegistry {
+    pub fn register(&mut self, num: u32, handler: Box<dyn SyscallHandler>) {
+        self.handlers.insert(num, handler);
+    }
+}
+
 pub struct BFVM {
     mode: BFMode,
     memory: Vec<u8>,
@@ -35,6 +153,16 @@ pub struct BFVM {
     execution_steps: u64,
     max_steps: u64,
     fds: Vec<OwnedFd>,
+    cost_table: CostTable,
+    compute_units_remaining: Option<u64>,
+    /// The budget `with_budget` started with, kept alongside
+    /// `compute_units_remaining` so `charge` can report how much was
+    /// actually spent rather than just the leftover balance.
+    compute_budget_total: Option<u64>,
+    heap_start: usize,
+    heap_size: usize,
+    max_memory: Option<usize>,
+    registry: SyscallRegistry,
 }
 
 impl BFVM {
@@ -48,9 +176,86 @@ impl BFVM {
             execution_steps: 0,
             max_steps: 1_000_000,
             fds: Vec::new(),
+            cost_table: CostTable::default(),
+            compute_units_remaining: None,
+            compute_budget_total: None,
+            heap_start: memory_size,
+            heap_size: 0,
+            max_memory: None,
+            registry: SyscallRegistry::default(),
+        }
+    }
+
+    /// Register a handler for a custom syscall number. A handler registered
+    /// for a number in the built-in POSIX range (0-17) takes priority over
+    /// the built-in implementation.
+    pub fn register_syscall(&mut self, num: u32, handler: Box<dyn SyscallHandler>) {
+        self.registry.register(num, handler);
+    }
+
+    pub fn arg(&self, index: usize) -> u32 {
+        self.get_syscall_arg(index)
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    pub fn memory_mut(&mut self) -> &mut [u8] {
+        &mut self.memory
+    }
+
+    /// Create a VM whose heap (grown via the `Brk` syscall) may not exceed
+    /// `max_memory` bytes in total.
+    pub fn with_max_memory(memory_size: usize, max_memory: usize) -> Self {
+        Self {
+            max_memory: Some(max_memory),
+            ..Self::new(memory_size)
         }
     }
 
+    pub fn heap_size(&self) -> usize {
+        self.heap_size
+    }
+
+    pub fn heap_start(&self) -> usize {
+        self.heap_start
+    }
+
+    /// Create a VM metered by a compute budget instead of (or in addition
+    /// to) the uniform step cap: each operation deducts its class's cost
+    /// from `units` and execution stops with `ComputeBudgetExceeded` once
+    /// the remaining balance can't cover the next op.
+    pub fn with_budget(memory_size: usize, units: u64, cost_table: CostTable) -> Self {
+        Self {
+            cost_table,
+            compute_units_remaining: Some(units),
+            compute_budget_total: Some(units),
+            ..Self::new(memory_size)
+        }
+    }
+
+    /// Remaining compute units, or `None` if the VM isn't budget-metered.
+    pub fn compute_units_remaining(&self) -> Option<u64> {
+        self.compute_units_remaining
+    }
+
+    fn charge(&mut self, cost: u64) -> VMResult<()> {
+        if let Some(remaining) = self.compute_units_remaining {
+            let result = remaining.checked_sub(cost).ok_or_else(|| {
+                let total = self.compute_budget_total.unwrap_or(remaining);
+                VMError::ComputeBudgetExceeded {
+                    units_consumed: total.saturating_sub(remaining),
+                }
+            });
+            match result {
+                Ok(new_remaining) => self.compute_units_remaining = Some(new_remaining),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
     pub fn run(&mut self, code: &str) -> Result<()> {
         while self.pc < code.len() && self.execution_steps < self.max_steps {
             self.execution_steps += 1;
@@ -69,12 +274,30 @@ impl BFVM {
 
     fn execute_normal(&mut self, code: &str) -> VMResult<()> {
         match code.chars().nth(self.pc).unwrap() {
-            '>' => self.increment_ptr()?,
-            '<' => self.decrement_ptr()?,
-            '+' => self.increment_value()?,
-            '-' => self.decrement_value()?,
-            '.' => self.output_char()?,
-            ',' => self.input_char()?,
+            '>' => {
+                self.charge(self.cost_table.pointer_op)?;
+                self.increment_ptr()?
+            }
+            '<' => {
+                self.charge(self.cost_table.pointer_op)?;
+                self.decrement_ptr()?
+            }
+            '+' => {
+                self.charge(self.cost_table.value_op)?;
+                self.increment_value()?
+            }
+            '-' => {
+                self.charge(self.cost_table.value_op)?;
+                self.decrement_value()?
+            }
+            '.' => {
+                self.charge(self.cost_table.io_op)?;
+                self.output_char()?
+            }
+            ',' => {
+                self.charge(self.cost_table.io_op)?;
+                self.input_char()?
+            }
             _ => {}
         }
         Ok(())
@@ -83,8 +306,29 @@ impl BFVM {
     fn execute_syscall(&mut self, code: &str) -> VMResult<()> {
         match code.chars().nth(self.pc).unwrap() {
             '.' => {
-                let syscall_number = self.get_syscall_number();
-                let syscall_number = SyscallNum::try_from(syscall_number)?;
+                let raw_syscall_number = self.get_syscall_number();
+
+                if let Some(mut handler) = self.registry.handlers.remove(&raw_syscall_number) {
+                    self.charge(self.cost_table.syscall_custom_default)?;
+                    let args = [
+                        self.get_syscall_arg(0),
+                        self.get_syscall_arg(1),
+                        self.get_syscall_arg(2),
+                        self.get_syscall_arg(3),
+                        self.get_syscall_arg(4),
+                        self.get_syscall_arg(5),
+                    ];
+                    let result = handler.invoke(self, &args);
+                    self.registry.handlers.insert(raw_syscall_number, handler);
+                    match result {
+                        Ok(value) => self.set_syscall_result(value),
+                        Err(e) => return Err(e),
+                    }
+                    return Ok(());
+                }
+
+                let syscall_number = SyscallNum::try_from(raw_syscall_number)?;
+                self.charge(self.cost_table.syscall_cost(syscall_number))?;
 
                 match syscall_number {
                     SyscallNum::Read => {
@@ -164,16 +408,24 @@ impl BFVM {
                     SyscallNum::Bind => {
                         let fd = self.get_syscall_arg(0) as usize;
                         let port = self.get_syscall_arg(1) as u16;
+                        // arg2, if non-zero, points at 4 raw address bytes
+                        // (network byte order) to bind to instead of the
+                        // wildcard address.
+                        let addr_offset = self.get_syscall_arg(2) as usize;
 
                         if fd >= self.fds.len() {
                             self.set_syscall_result(u32::MAX);
                             return Ok(());
                         }
 
-                        let addr = SockaddrIn::from(std::net::SocketAddrV4::new(
-                            Ipv4Addr::UNSPECIFIED,
-                            port,
-                        ));
+                        let ip = if addr_offset != 0 && addr_offset + 4 <= self.memory.len() {
+                            let b = &self.memory[addr_offset..addr_offset + 4];
+                            Ipv4Addr::new(b[0], b[1], b[2], b[3])
+                        } else {
+                            Ipv4Addr::UNSPECIFIED
+                        };
+
+                        let addr = SockaddrIn::from(std::net::SocketAddrV4::new(ip, port));
 
                         match socket::bind(self.fds[fd].as_raw_fd(), &addr) {
                             Ok(()) => {
@@ -221,6 +473,107 @@ impl BFVM {
                             }
                         }
                     }
+                    SyscallNum::Setsockopt => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let level = self.get_syscall_arg(1);
+                        let optname = self.get_syscall_arg(2);
+                        let optval = self.get_syscall_arg(3) != 0;
+
+                        if fd >= self.fds.len() {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        let result = match (level, optname) {
+                            (SOL_SOCKET, SO_REUSEADDR) => {
+                                socket::setsockopt(&self.fds[fd], sockopt::ReuseAddr, &optval)
+                            }
+                            (SOL_SOCKET, SO_REUSEPORT) => {
+                                socket::setsockopt(&self.fds[fd], sockopt::ReusePort, &optval)
+                            }
+                            _ => {
+                                self.set_syscall_result(u32::MAX);
+                                return Ok(());
+                            }
+                        };
+
+                        match result {
+                            Ok(()) => self.set_syscall_result(0),
+                            Err(_) => self.set_syscall_result(u32::MAX),
+                        }
+                    }
+                    SyscallNum::Accept4 => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let flags = SockFlag::from_bits_truncate(self.get_syscall_arg(1) as i32);
+
+                        if fd >= self.fds.len() {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        match socket::accept4(self.fds[fd].as_raw_fd(), flags) {
+                            Ok(new_fd) => {
+                                self.fds.push(unsafe { OwnedFd::from_raw_fd(new_fd) });
+                                self.set_syscall_result((self.fds.len() - 1) as u32);
+                            }
+                            Err(_) => {
+                                self.set_syscall_result(u32::MAX);
+                            }
+                        }
+                    }
+                    SyscallNum::Sendto => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let buf_offset = self.get_syscall_arg(1) as usize;
+                        let len = self.get_syscall_arg(2) as usize;
+                        let dest_ip = self.get_syscall_arg(3);
+                        let dest_port = self.get_syscall_arg(4) as u16;
+
+                        if fd >= self.fds.len() || buf_offset + len > self.memory.len() {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        let dest = SockaddrIn::from(std::net::SocketAddrV4::new(
+                            Ipv4Addr::from(dest_ip),
+                            dest_port,
+                        ));
+                        let buf = &self.memory[buf_offset..buf_offset + len];
+
+                        match socket::sendto(self.fds[fd].as_raw_fd(), buf, &dest, socket::MsgFlags::empty()) {
+                            Ok(n) => self.set_syscall_result(n as u32),
+                            Err(_) => self.set_syscall_result(u32::MAX),
+                        }
+                    }
+                    SyscallNum::Recvfrom => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let buf_offset = self.get_syscall_arg(1) as usize;
+                        let capacity = self.get_syscall_arg(2) as usize;
+                        let peer_offset = self.get_syscall_arg(3) as usize;
+
+                        if fd >= self.fds.len()
+                            || buf_offset + capacity > self.memory.len()
+                            || peer_offset + 6 > self.memory.len()
+                        {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        let mut buf = vec![0u8; capacity];
+                        match socket::recvfrom::<SockaddrIn>(self.fds[fd].as_raw_fd(), &mut buf) {
+                            Ok((n, peer)) => {
+                                self.memory[buf_offset..buf_offset + n].copy_from_slice(&buf[..n]);
+                                if let Some(peer) = peer {
+                                    let ip_octets = peer.ip().octets();
+                                    self.memory[peer_offset..peer_offset + 4]
+                                        .copy_from_slice(&ip_octets);
+                                    self.memory[peer_offset + 4..peer_offset + 6]
+                                        .copy_from_slice(&peer.port().to_ne_bytes());
+                                }
+                                self.set_syscall_result(n as u32);
+                            }
+                            Err(_) => self.set_syscall_result(u32::MAX),
+                        }
+                    }
                     SyscallNum::Close => {
                         let fd = self.get_syscall_arg(0) as usize;
 
@@ -233,6 +586,98 @@ impl BFVM {
                         self.fds.remove(fd);
                         self.set_syscall_result(0);
                     }
+                    SyscallNum::GetComputeUnits => {
+                        // Lets a running program self-meter against its own budget.
+                        let remaining = self.compute_units_remaining.unwrap_or(u32::MAX as u64);
+                        self.set_syscall_result(remaining.min(u32::MAX as u64) as u32);
+                    }
+                    SyscallNum::Sha256 => {
+                        self.hash_memory_region(|input| Sha256::digest(input).into());
+                    }
+                    SyscallNum::Keccak256 => {
+                        self.hash_memory_region(|input| Keccak256::digest(input).into());
+                    }
+                    SyscallNum::Blake3 => {
+                        self.hash_memory_region(|input| *blake3::hash(input).as_bytes());
+                    }
+                    SyscallNum::Readv => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let iovec_ptr = self.get_syscall_arg(1) as usize;
+                        let count = self.get_syscall_arg(2) as usize;
+
+                        if fd >= self.fds.len() {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        let Some(regions) = self.read_iovecs(iovec_ptr, count) else {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        };
+
+                        let mut scratch: Vec<Vec<u8>> =
+                            regions.iter().map(|&(_, len)| vec![0u8; len]).collect();
+                        let mut io_slices: Vec<IoSliceMut> =
+                            scratch.iter_mut().map(|b| IoSliceMut::new(b)).collect();
+
+                        match readv(&self.fds[fd], &mut io_slices) {
+                            Ok(n) => {
+                                let mut remaining = n;
+                                for (&(buf_off, len), chunk) in regions.iter().zip(scratch.iter())
+                                {
+                                    let take = len.min(remaining);
+                                    self.memory[buf_off..buf_off + take]
+                                        .copy_from_slice(&chunk[..take]);
+                                    remaining -= take;
+                                }
+                                self.set_syscall_result(n as u32);
+                            }
+                            Err(_) => self.set_syscall_result(u32::MAX),
+                        }
+                    }
+                    SyscallNum::Brk => {
+                        let additional = self.get_syscall_arg(0) as usize;
+                        let Some(new_len) = self.memory.len().checked_add(additional) else {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        };
+
+                        if self.max_memory.is_some_and(|max| new_len > max) {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        self.memory.resize(new_len, 0);
+                        self.heap_size += additional;
+                        self.set_syscall_result(new_len as u32);
+                    }
+                    SyscallNum::Writev => {
+                        let fd = self.get_syscall_arg(0) as usize;
+                        let iovec_ptr = self.get_syscall_arg(1) as usize;
+                        let count = self.get_syscall_arg(2) as usize;
+
+                        if fd >= self.fds.len() {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        }
+
+                        let Some(regions) = self.read_iovecs(iovec_ptr, count) else {
+                            self.set_syscall_result(u32::MAX);
+                            return Ok(());
+                        };
+
+                        let buffers: Vec<Vec<u8>> = regions
+                            .iter()
+                            .map(|&(buf_off, len)| self.memory[buf_off..buf_off + len].to_vec())
+                            .collect();
+                        let io_slices: Vec<IoSlice> =
+                            buffers.iter().map(|b| IoSlice::new(b)).collect();
+
+                        match writev(&self.fds[fd], &io_slices) {
+                            Ok(n) => self.set_syscall_result(n as u32),
+                            Err(_) => self.set_syscall_result(u32::MAX),
+                        }
+                    }
                 }
             }
             _ => self.execute_normal(code)?,
@@ -240,6 +685,55 @@ impl BFVM {
         Ok(())
     }
 
+    /// Parses `count` little-endian `(offset, length)` iovec pairs starting
+    /// at `iovec_ptr` in `memory`, returning `None` if the descriptor array
+    /// or any referenced region runs past the end of memory.
+    fn read_iovecs(&self, iovec_ptr: usize, count: usize) -> Option<Vec<(usize, usize)>> {
+        let mut regions = Vec::with_capacity(count);
+        for i in 0..count {
+            let entry_off = iovec_ptr + i * 8;
+            if entry_off + 8 > self.memory.len() {
+                return None;
+            }
+            let buf_off =
+                u32::from_le_bytes(self.memory[entry_off..entry_off + 4].try_into().unwrap())
+                    as usize;
+            let len = u32::from_le_bytes(
+                self.memory[entry_off + 4..entry_off + 8].try_into().unwrap(),
+            ) as usize;
+            if buf_off.checked_add(len)? > self.memory.len() {
+                return None;
+            }
+            regions.push((buf_off, len));
+        }
+        Some(regions)
+    }
+
+    // `arg0` = input offset, `arg1` = input length, `arg2` = output offset.
+    // Bounds-checked against `self.memory.len()`; writes `u32::MAX` on overflow.
+    fn hash_memory_region(&mut self, digest: impl FnOnce(&[u8]) -> [u8; 32]) {
+        let in_offset = self.get_syscall_arg(0) as usize;
+        let in_len = self.get_syscall_arg(1) as usize;
+        let out_offset = self.get_syscall_arg(2) as usize;
+
+        let Some(in_end) = in_offset.checked_add(in_len) else {
+            self.set_syscall_result(u32::MAX);
+            return;
+        };
+        let Some(out_end) = out_offset.checked_add(32) else {
+            self.set_syscall_result(u32::MAX);
+            return;
+        };
+        if in_end > self.memory.len() || out_end > self.memory.len() {
+            self.set_syscall_result(u32::MAX);
+            return;
+        }
+
+        let hash = digest(&self.memory[in_offset..in_end]);
+        self.memory[out_offset..out_end].copy_from_slice(&hash);
+        self.set_syscall_result(32);
+    }
+
     // Memory safety methods
     fn check_bounds(&self, ptr: u32) -> VMResult<()> {
         if ptr >= self.memory.len() as u32 {
@@ -299,7 +793,7 @@ impl BFVM {
         )
     }
 
-    fn set_syscall_result(&mut self, value: u32) {
+    pub fn set_syscall_result(&mut self, value: u32) {
         self.memory[SYSCALL_RESULT_OFFSET..SYSCALL_RESULT_OFFSET + 4]
             .copy_from_slice(&value.to_ne_bytes());
     }
@@ -1,4 +1,7 @@
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
 use thiserror::Error;
 
 /// VM-specific errors that we want to handle specifically
@@ -10,6 +13,9 @@ pub enum VMError {
     InvalidSyscall,
     #[error("Execution timeout")]
     ExecutionTimeout,
+    #[error("Compute budget exceeded after consuming {units_consumed} units")]
+    ComputeBudgetExceeded { units_consumed: u64 },
+    #[cfg(feature = "std")]
     #[error("IO error: {0}")]
     IoError(#[from] io::Error),
 }
@@ -30,10 +36,10 @@ pub enum CompileError {
 }
 
 // For VM operations where we want specific error handling
-pub type VMResult<T> = std::result::Result<T, VMError>;
+pub type VMResult<T> = core::result::Result<T, VMError>;
 
 // For compiler operations where we want specific error handling
-pub type CompileResult<T> = std::result::Result<T, CompileError>;
+pub type CompileResult<T> = core::result::Result<T, CompileError>;
 
 // For general operations where we want to propagate errors with anyhow
 pub type Result<T> = anyhow::Result<T>;
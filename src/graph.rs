@@ -0,0 +1,45 @@
+//! A minimal undirected-graph primitive, in the spirit of the `ugraphs`
+//! crate: adjacency sets keyed by a plain `usize` node id, with edge
+//! insertion and neighbor iteration. `bfl`'s interference-graph coloring is
+//! the only consumer so far.
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeMap as HashMap;
+#[cfg(not(feature = "std"))]
+use alloc::collections::BTreeSet as HashSet;
+
+#[derive(Debug, Default, Clone)]
+pub struct Graph {
+    adjacency: HashMap<usize, HashSet<usize>>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(&mut self, node: usize) {
+        self.adjacency.entry(node).or_default();
+    }
+
+    pub fn add_edge(&mut self, a: usize, b: usize) {
+        if a == b {
+            return;
+        }
+        self.adjacency.entry(a).or_default().insert(b);
+        self.adjacency.entry(b).or_default().insert(a);
+    }
+
+    pub fn neighbors(&self, node: usize) -> impl Iterator<Item = &usize> {
+        self.adjacency.get(&node).into_iter().flatten()
+    }
+
+    pub fn degree(&self, node: usize) -> usize {
+        self.adjacency.get(&node).map_or(0, HashSet::len)
+    }
+}
@@ -0,0 +1,59 @@
+//! A minimal `Read`/`Write` trait pair, modeled on the `core_io` crate, so
+//! `BF`'s I/O can be routed through a user-supplied transport instead of
+//! `std::io` on a host built without the `std` feature.
+//!
+//! `bf` still leans on `std::collections::HashMap` elsewhere, so the
+//! crate as a whole isn't fully `no_std`-clean yet - this module is the
+//! first slice: the I/O boundary a bare-metal host actually needs to supply.
+
+extern crate alloc;
+
+use alloc::string::String;
+
+/// Why a `Read`/`Write` call failed. Just a message: without
+/// `std::io::Error`'s OS error codes, there's nothing richer to report on a
+/// bare-metal host with no `errno`.
+#[derive(Debug)]
+pub struct IoError(pub String);
+
+impl core::fmt::Display for IoError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for IoError {}
+
+/// A byte source - `std::io::Read`'s `no_std`-compatible equivalent.
+pub trait Read {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError>;
+}
+
+/// A byte sink - `std::io::Write`'s `no_std`-compatible equivalent.
+pub trait Write {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError>;
+}
+
+/// Bridges real stdin/stdout into the `Read`/`Write` pair above, for hosts
+/// that have `std` and just want today's default behavior.
+#[cfg(feature = "std")]
+pub struct StdIo;
+
+#[cfg(feature = "std")]
+impl Read for StdIo {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, IoError> {
+        std::io::Read::read(&mut std::io::stdin(), buf).map_err(|e| IoError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "std")]
+impl Write for StdIo {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, IoError> {
+        use std::io::Write as _;
+        let mut stdout = std::io::stdout();
+        stdout.write_all(buf).map_err(|e| IoError(e.to_string()))?;
+        stdout.flush().map_err(|e| IoError(e.to_string()))?;
+        Ok(buf.len())
+    }
+}
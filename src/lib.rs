@@ -1,7 +1,21 @@
+// `no_std` behind the `std` feature (on by default): `bfvm` and the `syscalls`-
+// backed parts of `bf`'s `RealIoBackend` assume a real OS, so only `io`'s
+// transport abstraction and `bf`/`bfl`'s non-OS logic are meant to build
+// without it. See `io` module docs for the current state of that split.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+extern crate alloc;
+
 // Re-export modules
+pub mod bfir;
 pub mod bfl;
+#[cfg(feature = "std")]
 pub mod bfvm;
 pub mod errors;
+pub mod graph;
+pub mod io;
+pub mod syscall_consts;
 
 pub use errors::{Result, VMError, VMResult};
 
@@ -15,12 +29,43 @@ pub enum SyscallNum {
     Listen = 4,
     Accept = 5,
     Close = 6,
+    /// Writes the remaining compute budget into the syscall-result slot.
+    GetComputeUnits = 7,
+    /// Hashes `memory[arg0..arg0+arg1]` with SHA-256, writing the 32-byte
+    /// digest to offset `arg2`.
+    Sha256 = 8,
+    /// Hashes `memory[arg0..arg0+arg1]` with Keccak-256, writing the 32-byte
+    /// digest to offset `arg2`.
+    Keccak256 = 9,
+    /// Hashes `memory[arg0..arg0+arg1]` with BLAKE3, writing the 32-byte
+    /// digest to offset `arg2`.
+    Blake3 = 10,
+    /// Scatters bytes read from `arg0` into the iovec array at `arg1`
+    /// (`arg2` entries).
+    Readv = 11,
+    /// Gathers the iovec array at `arg1` (`arg2` entries) into one write to
+    /// `arg0`.
+    Writev = 12,
+    /// Grows the heap by `arg0` bytes, returning the new end address (or
+    /// `u32::MAX` if the configured `max_memory` ceiling would be exceeded).
+    Brk = 13,
+    /// `arg0` = fd, `arg1` = level, `arg2` = optname, `arg3` = int optval.
+    Setsockopt = 14,
+    /// Like `Accept`, but `arg1` carries `SOCK_NONBLOCK`/`SOCK_CLOEXEC` flags
+    /// to OR into the accepted socket.
+    Accept4 = 15,
+    /// `arg0` = fd, `arg1` = buf offset, `arg2` = length, `arg3` = dest IPv4
+    /// (packed `u32`), `arg4` = dest port.
+    Sendto = 16,
+    /// `arg0` = fd, `arg1` = buf offset, `arg2` = buf capacity, `arg3` =
+    /// offset to write the peer's packed IPv4 address + port after the data.
+    Recvfrom = 17,
 }
 
 impl TryFrom<u32> for SyscallNum {
     type Error = VMError;
 
-    fn try_from(value: u32) -> std::result::Result<SyscallNum, errors::VMError> {
+    fn try_from(value: u32) -> VMResult<SyscallNum> {
         Ok(match value {
             0 => SyscallNum::Read,
             1 => SyscallNum::Write,
@@ -29,6 +74,17 @@ impl TryFrom<u32> for SyscallNum {
             4 => SyscallNum::Listen,
             5 => SyscallNum::Accept,
             6 => SyscallNum::Close,
+            7 => SyscallNum::GetComputeUnits,
+            8 => SyscallNum::Sha256,
+            9 => SyscallNum::Keccak256,
+            10 => SyscallNum::Blake3,
+            11 => SyscallNum::Readv,
+            12 => SyscallNum::Writev,
+            13 => SyscallNum::Brk,
+            14 => SyscallNum::Setsockopt,
+            15 => SyscallNum::Accept4,
+            16 => SyscallNum::Sendto,
+            17 => SyscallNum::Recvfrom,
             _ => return Err(VMError::InvalidSyscall.into()),
         })
     }
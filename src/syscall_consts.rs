@@ -1,5 +1,10 @@
 //! Portable syscall constants that work across Linux and macOS
 
+#[cfg(not(feature = "std"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
 #[cfg(target_os = "linux")]
 pub const SYS_WRITE: i32 = 1;
 #[cfg(target_os = "macos")]
@@ -30,11 +35,111 @@ pub const SYS_LISTEN: i32 = 50;
 #[cfg(target_os = "macos")]
 pub const SYS_LISTEN: i32 = 106;
 
+#[cfg(target_os = "linux")]
+pub const SYS_CONNECT: i32 = 42;
+#[cfg(target_os = "macos")]
+pub const SYS_CONNECT: i32 = 98;
+
 #[cfg(target_os = "linux")]
 pub const SYS_ACCEPT: i32 = 43;
 #[cfg(target_os = "macos")]
 pub const SYS_ACCEPT: i32 = 30;
 
+#[cfg(target_os = "linux")]
+pub const SYS_READV: i32 = 19;
+#[cfg(target_os = "macos")]
+pub const SYS_READV: i32 = 120;
+
+#[cfg(target_os = "linux")]
+pub const SYS_WRITEV: i32 = 20;
+#[cfg(target_os = "macos")]
+pub const SYS_WRITEV: i32 = 121;
+
+#[cfg(target_os = "linux")]
+pub const SYS_POLL: i32 = 7;
+#[cfg(target_os = "macos")]
+pub const SYS_POLL: i32 = 230;
+
+#[cfg(target_os = "linux")]
+pub const SYS_SENDTO: i32 = 44;
+#[cfg(target_os = "macos")]
+pub const SYS_SENDTO: i32 = 133;
+
+#[cfg(target_os = "linux")]
+pub const SYS_RECVFROM: i32 = 45;
+#[cfg(target_os = "macos")]
+pub const SYS_RECVFROM: i32 = 29;
+
 // Common constants
 pub const AF_INET: i32 = 2;
-pub const SOCK_STREAM: i32 = 1; 
\ No newline at end of file
+pub const SOCK_STREAM: i32 = 1;
+/// Connectionless datagram sockets - `sendto`/`recvfrom` pass the peer
+/// address per message instead of relying on a connected fd the way
+/// `SOCK_STREAM` does.
+pub const SOCK_DGRAM: i32 = 2;
+
+/// `struct pollfd.revents` bit meaning "ready to read" - the flag a
+/// single-threaded event loop tests for after `poll` returns to decide
+/// which fd to service.
+pub const POLLIN: i32 = 0x0001;
+
+// Pseudo-syscalls with no kernel equivalent: numbered well above any real
+// syscall table entry on either platform so they can never collide.
+pub const SYS_SHA256: i32 = 1000;
+pub const SYS_KECCAK256: i32 = 1001;
+pub const SYS_BLAKE3: i32 = 1002;
+pub const SYS_SECP256K1_RECOVER: i32 = 1003;
+pub const SYS_PEEK: i32 = 1004;
+pub const SYS_POKE: i32 = 1005;
+
+/// A raw syscall's failure is encoded into `_syscall_result` as
+/// `SYSCALL_ERROR_BASE + errno` rather than literal two's-complement
+/// `-errno` - see `BF::encode_syscall_result` for why. Comfortably above
+/// any byte count, fd, or ready count a syscall in this VM returns on
+/// success.
+pub const SYSCALL_ERROR_BASE: i32 = 10_000;
+
+// Common errno values, for BFL programs that want to branch on *which*
+// error a syscall failed with (e.g. retry on EAGAIN). Values match the
+// platform's own `<errno.h>`, which diverges between Linux and macOS past
+// the first handful of codes.
+pub const EPERM: i32 = 1;
+pub const ENOENT: i32 = 2;
+pub const EINTR: i32 = 4;
+pub const EIO: i32 = 5;
+pub const EBADF: i32 = 9;
+
+#[cfg(target_os = "linux")]
+pub const EAGAIN: i32 = 11;
+#[cfg(target_os = "macos")]
+pub const EAGAIN: i32 = 35;
+
+#[cfg(target_os = "linux")]
+pub const EADDRINUSE: i32 = 98;
+#[cfg(target_os = "macos")]
+pub const EADDRINUSE: i32 = 48;
+
+#[cfg(target_os = "linux")]
+pub const ECONNREFUSED: i32 = 111;
+#[cfg(target_os = "macos")]
+pub const ECONNREFUSED: i32 = 61;
+
+/// Human-readable description for a raw errno value, for driver programs
+/// that want to print `Server error: Address already in use` instead of a
+/// bare number. Falls back to `Unknown error <errno>` for anything not in
+/// this table - deliberately small rather than exhaustive, covering the errors
+/// this crate's own syscalls (`bind`/`connect`/`accept`/`read`/`poll`, ...)
+/// actually surface.
+pub fn errno_message(errno: i32) -> String {
+    match errno {
+        EPERM => "Operation not permitted".to_string(),
+        ENOENT => "No such file or directory".to_string(),
+        EINTR => "Interrupted system call".to_string(),
+        EIO => "Input/output error".to_string(),
+        EBADF => "Bad file descriptor".to_string(),
+        EAGAIN => "Resource temporarily unavailable".to_string(),
+        EADDRINUSE => "Address already in use".to_string(),
+        ECONNREFUSED => "Connection refused".to_string(),
+        other => format!("Unknown error {other}"),
+    }
+}
@@ -1,5 +1,7 @@
-use ewor_brainfuck::bf::{BF, Mode};
-use ewor_brainfuck::bfl::{BFLCompiler, BFLNode};
+use ewor_brainfuck::bf::{BF, BFError, MockIoBackend, Mode};
+use ewor_brainfuck::bfir::BfOp;
+use ewor_brainfuck::bfl::{BFLCompiler, BFLNode, BflError, DisasmError};
+use ewor_brainfuck::syscall_consts::{EAGAIN, POLLIN, SYSCALL_ERROR_BASE};
 
 #[test]
 fn test_bfl_if_simple_condition() {
@@ -142,6 +144,182 @@ fn test_bfl_sub() {
     assert_eq!(bf.dump_cells(result_addr + 1)[result_addr], 7);
 }
 
+#[test]
+fn test_bfl_mul() {
+    // Test Mul operation
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("result".to_string(), Box::new(BFLNode::Mul(
+            Box::new(BFLNode::Number(6)),
+            Box::new(BFLNode::Number(7)),
+        ))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let result_addr = compiler.get_variable_address("result").unwrap();
+    assert_eq!(bf.dump_cells(result_addr + 1)[result_addr], 42);
+}
+
+#[test]
+fn test_bfl_mul_nested_operand() {
+    // Mul(2, Mul(3, 4)) == 24 - the RHS operand is itself a Mul, which
+    // must not clobber the already-evaluated LHS held in the same scratch
+    // cell a flat Mul would reuse (see `held_operand_cell`).
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("result".to_string(), Box::new(BFLNode::Mul(
+            Box::new(BFLNode::Number(2)),
+            Box::new(BFLNode::Mul(
+                Box::new(BFLNode::Number(3)),
+                Box::new(BFLNode::Number(4)),
+            )),
+        ))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let result_addr = compiler.get_variable_address("result").unwrap();
+    assert_eq!(bf.dump_cells(result_addr + 1)[result_addr], 24);
+}
+
+#[test]
+fn test_bfl_div_mod() {
+    // Test Div and Mod operations
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("quotient".to_string(), Box::new(BFLNode::Div(
+            Box::new(BFLNode::Number(17)),
+            Box::new(BFLNode::Number(5)),
+        ))),
+        BFLNode::Assign("remainder".to_string(), Box::new(BFLNode::Mod(
+            Box::new(BFLNode::Number(17)),
+            Box::new(BFLNode::Number(5)),
+        ))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let quotient_addr = compiler.get_variable_address("quotient").unwrap();
+    let remainder_addr = compiler.get_variable_address("remainder").unwrap();
+    assert_eq!(bf.dump_cells(quotient_addr + 1)[quotient_addr], 3);
+    assert_eq!(bf.dump_cells(remainder_addr + 1)[remainder_addr], 2);
+}
+
+#[test]
+fn test_bfl_comparisons() {
+    // Test Eq, Neq, Lt, Gt
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("eq".to_string(), Box::new(BFLNode::Eq(
+            Box::new(BFLNode::Number(3)),
+            Box::new(BFLNode::Number(3)),
+        ))),
+        BFLNode::Assign("neq".to_string(), Box::new(BFLNode::Neq(
+            Box::new(BFLNode::Number(3)),
+            Box::new(BFLNode::Number(4)),
+        ))),
+        BFLNode::Assign("lt".to_string(), Box::new(BFLNode::Lt(
+            Box::new(BFLNode::Number(3)),
+            Box::new(BFLNode::Number(4)),
+        ))),
+        BFLNode::Assign("gt".to_string(), Box::new(BFLNode::Gt(
+            Box::new(BFLNode::Number(4)),
+            Box::new(BFLNode::Number(3)),
+        ))),
+        BFLNode::Assign("not_lt".to_string(), Box::new(BFLNode::Lt(
+            Box::new(BFLNode::Number(4)),
+            Box::new(BFLNode::Number(3)),
+        ))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let eq_addr = compiler.get_variable_address("eq").unwrap();
+    let neq_addr = compiler.get_variable_address("neq").unwrap();
+    let lt_addr = compiler.get_variable_address("lt").unwrap();
+    let gt_addr = compiler.get_variable_address("gt").unwrap();
+    let not_lt_addr = compiler.get_variable_address("not_lt").unwrap();
+    assert_eq!(bf.dump_cells(eq_addr + 1)[eq_addr], 1);
+    assert_eq!(bf.dump_cells(neq_addr + 1)[neq_addr], 1);
+    assert_eq!(bf.dump_cells(lt_addr + 1)[lt_addr], 1);
+    assert_eq!(bf.dump_cells(gt_addr + 1)[gt_addr], 1);
+    assert_eq!(bf.dump_cells(not_lt_addr + 1)[not_lt_addr], 0);
+}
+
+#[test]
+fn test_bfl_logical_ops() {
+    // Test And, Or, Not
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("and_true".to_string(), Box::new(BFLNode::And(
+            Box::new(BFLNode::Number(5)),
+            Box::new(BFLNode::Number(2)),
+        ))),
+        BFLNode::Assign("and_false".to_string(), Box::new(BFLNode::And(
+            Box::new(BFLNode::Number(1)),
+            Box::new(BFLNode::Number(0)),
+        ))),
+        BFLNode::Assign("or_true".to_string(), Box::new(BFLNode::Or(
+            Box::new(BFLNode::Number(0)),
+            Box::new(BFLNode::Number(3)),
+        ))),
+        BFLNode::Assign("not_zero".to_string(), Box::new(BFLNode::Not(
+            Box::new(BFLNode::Number(0)),
+        ))),
+        BFLNode::Assign("not_nonzero".to_string(), Box::new(BFLNode::Not(
+            Box::new(BFLNode::Number(5)),
+        ))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let and_true_addr = compiler.get_variable_address("and_true").unwrap();
+    let and_false_addr = compiler.get_variable_address("and_false").unwrap();
+    let or_true_addr = compiler.get_variable_address("or_true").unwrap();
+    let not_zero_addr = compiler.get_variable_address("not_zero").unwrap();
+    let not_nonzero_addr = compiler.get_variable_address("not_nonzero").unwrap();
+    assert_eq!(bf.dump_cells(and_true_addr + 1)[and_true_addr], 1);
+    assert_eq!(bf.dump_cells(and_false_addr + 1)[and_false_addr], 0);
+    assert_eq!(bf.dump_cells(or_true_addr + 1)[or_true_addr], 1);
+    assert_eq!(bf.dump_cells(not_zero_addr + 1)[not_zero_addr], 1);
+    assert_eq!(bf.dump_cells(not_nonzero_addr + 1)[not_nonzero_addr], 0);
+}
+
+#[test]
+fn test_bfl_while_with_lt_condition() {
+    // Test a counting While loop driven directly by a Lt comparison node,
+    // per chunk3-5's "make If/While conditions use these comparison nodes
+    // directly" requirement.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("i".to_string(), Box::new(BFLNode::Number(0))),
+        BFLNode::While(
+            Box::new(BFLNode::Lt(
+                Box::new(BFLNode::Variable("i".to_string())),
+                Box::new(BFLNode::Number(5)),
+            )),
+            vec![
+                BFLNode::Assign("i".to_string(), Box::new(BFLNode::Add(
+                    Box::new(BFLNode::Variable("i".to_string())),
+                    Box::new(BFLNode::Number(1)),
+                ))),
+            ],
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let i_addr = compiler.get_variable_address("i").unwrap();
+    assert_eq!(bf.dump_cells(i_addr + 1)[i_addr], 5);
+}
+
 #[test]
 fn test_bfl_simple_assignment() {
     // Test basic assignment without any control flow
@@ -185,9 +363,103 @@ fn test_bfl_minimal_if() {
 }
 
 #[test]
-#[ignore]
+fn test_bfl_analyze_accepts_valid_program() {
+    let compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("msg".to_string(), Box::new(BFLNode::String("Hello, BFL!\n".to_string()))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::Number(1)),
+            vec![
+                BFLNode::Number(1),
+                BFLNode::Variable("msg".to_string()),
+                BFLNode::Number(12),
+            ],
+        ),
+    ]);
+    compiler.analyze(&program).unwrap();
+}
+
+#[test]
+fn test_bfl_analyze_buffer_length_mismatch() {
+    let compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("msg".to_string(), Box::new(BFLNode::String("Hello, BFL!\n".to_string()))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::Number(1)),
+            vec![
+                BFLNode::Number(1),
+                BFLNode::Variable("msg".to_string()),
+                BFLNode::Number(999),
+            ],
+        ),
+    ]);
+    let errors = compiler.analyze(&program).unwrap_err();
+    assert!(errors.iter().any(|e| matches!(
+        e,
+        BflError::BufferLengthMismatch { declared: 12, used: 999, .. }
+    )));
+}
+
+#[test]
+fn test_bfl_analyze_type_mismatch() {
+    let compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("msg".to_string(), Box::new(BFLNode::String("hi".to_string()))),
+        BFLNode::Assign(
+            "bad".to_string(),
+            Box::new(BFLNode::Add(
+                Box::new(BFLNode::Variable("msg".to_string())),
+                Box::new(BFLNode::Number(1)),
+            )),
+        ),
+    ]);
+    let errors = compiler.analyze(&program).unwrap_err();
+    assert!(errors.iter().any(|e| matches!(e, BflError::TypeMismatch { .. })));
+}
+
+#[test]
+fn test_bfl_analyze_undefined_variable() {
+    let compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![BFLNode::Assign(
+        "x".to_string(),
+        Box::new(BFLNode::Variable("never_assigned".to_string())),
+    )]);
+    let errors = compiler.analyze(&program).unwrap_err();
+    assert!(errors.iter().any(
+        |e| matches!(e, BflError::UndefinedVariable { name, .. } if name == "never_assigned")
+    ));
+}
+
+#[test]
+fn test_bfl_buffer_access_violation() {
+    // "msg" is only allocated 4 bytes, but the syscall claims 999 - the
+    // interpreter should reject the over-long length instead of silently
+    // reading whatever happens to follow it in memory.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("msg".to_string(), Box::new(BFLNode::Bytes(vec![1, 2, 3, 4]))),
+        BFLNode::Syscall(
+            Box::new(BFLNode::Number(1)),
+            vec![
+                BFLNode::Number(1),
+                BFLNode::Variable("msg".to_string()),
+                BFLNode::Number(999),
+            ],
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::with_memory_regions(bf_code, Mode::BFA, compiler.get_regions());
+    match bf.run() {
+        Err(BFError::AccessViolation { len, .. }) => assert_eq!(len, 999),
+        other => panic!("Expected AccessViolation, got: {:?}", other),
+    }
+}
+
+#[test]
 fn test_bfl_syscall_read() {
-    // This test is ignored by default because it requires user input.
+    // Backed by a MockIoBackend instead of real stdin, so the echoed bytes
+    // are deterministic and assertable.
     let mut compiler = BFLCompiler::new();
     let program = BFLNode::Block(vec![
         BFLNode::Assign("buf".to_string(), Box::new(BFLNode::Bytes(vec![0; 8]))),
@@ -210,15 +482,16 @@ fn test_bfl_syscall_read() {
     ]);
     compiler.compile(&program).unwrap();
     let bf_code = compiler.get_output();
-    let mut bf = BF::new(bf_code, Mode::BFA);
+    let mut bf = BF::with_backend(bf_code, Mode::BFA, Box::new(MockIoBackend::with_stdin(b"ABCDEFGH")));
     bf.run().unwrap();
-    // Manually check that input is echoed back
+    let backend = bf.backend().as_any().downcast_ref::<MockIoBackend>().unwrap();
+    assert_eq!(backend.output(1), b"ABCDEFGH");
 }
 
 #[test]
-#[ignore]
 fn test_bfl_network_socket() {
-    // This test is ignored by default because it requires network permissions.
+    // Backed by a MockIoBackend instead of a real socket, so this never
+    // touches the network and the fake fd is assertable.
     let mut compiler = BFLCompiler::new();
     let program = BFLNode::Block(vec![
         BFLNode::Syscall(
@@ -237,7 +510,398 @@ fn test_bfl_network_socket() {
     ]);
     compiler.compile(&program).unwrap();
     let bf_code = compiler.get_output();
+    let mut bf = BF::with_backend(bf_code, Mode::BFA, Box::new(MockIoBackend::default()));
+    bf.run().unwrap();
+    let fd_addr = compiler.get_variable_address("fd").unwrap();
+    assert_eq!(bf.dump_cells(fd_addr + 1)[fd_addr], 100);
+}
+
+/// Reads a little-endian `width`-limb wide integer back out of `bf`'s cells.
+fn read_wide(bf: &BF, base: usize, width: usize) -> u64 {
+    let cells = bf.dump_cells(base + width);
+    (0..width).fold(0u64, |acc, i| acc | ((cells[base + i] as u64) << (8 * i)))
+}
+
+#[test]
+fn test_bfl_wide_add_overflows_into_next_limb() {
+    // 255 + 255 = 510, which doesn't fit in one limb - carry should land in
+    // limb 1, leaving limb 0 holding 510 % 256 = 254.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![BFLNode::Assign(
+        "sum".to_string(),
+        Box::new(BFLNode::WideAdd(
+            Box::new(BFLNode::WideNumber(255, 2)),
+            Box::new(BFLNode::WideNumber(255, 2)),
+            2,
+        )),
+    )]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let sum_addr = compiler.get_variable_address("sum").unwrap();
+    assert_eq!(read_wide(&bf, sum_addr, 2), 510);
+}
+
+#[test]
+fn test_bfl_wide_sub_borrows_across_limb() {
+    // 0x0105 - 0x0006 = 0x00FF, which requires borrowing from limb 1 since
+    // limb 0 alone (0x05 - 0x06) would go negative.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![BFLNode::Assign(
+        "diff".to_string(),
+        Box::new(BFLNode::WideSub(
+            Box::new(BFLNode::WideNumber(0x0105, 2)),
+            Box::new(BFLNode::WideNumber(0x0006, 2)),
+            2,
+        )),
+    )]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let diff_addr = compiler.get_variable_address("diff").unwrap();
+    assert_eq!(read_wide(&bf, diff_addr, 2), 0x00FF);
+}
+
+#[test]
+fn test_bfl_disasm_covers_every_statement_in_order() {
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("x".to_string(), Box::new(BFLNode::Number(1))),
+        BFLNode::While(
+            Box::new(BFLNode::Variable("x".to_string())),
+            vec![BFLNode::Assign("x".to_string(), Box::new(BFLNode::Number(0)))],
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+
+    let spans = compiler.disasm();
+    assert_eq!(spans.len(), 3);
+    // Sorted by start offset, so the While's span (which starts before its
+    // body) sits between the outer assign and the inner one it encloses.
+    assert!(spans.windows(2).all(|w| w[0].start <= w[1].start));
+
+    let outer_assign = spans.iter().find(|s| s.depth == 0 && s.description.contains("Assign x")).unwrap();
+    let while_span = spans.iter().find(|s| s.description.contains("While cond")).unwrap();
+    let inner_assign = spans.iter().find(|s| s.depth == 1).unwrap();
+    assert!(inner_assign.description.contains("Assign x"));
+    // The while loop's recorded range encloses the assign inside its body.
+    assert!(while_span.start <= inner_assign.start && inner_assign.end <= while_span.end);
+    assert!(outer_assign.end <= while_span.start);
+
+    let rendered = compiler.render_annotated();
+    assert!(rendered.contains("; Assign x"));
+    assert!(rendered.contains("; While cond"));
+}
+
+#[test]
+fn test_bfl_node_at_offset_round_trips_disasm() {
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![BFLNode::Assign(
+        "y".to_string(),
+        Box::new(BFLNode::Number(42)),
+    )]);
+    compiler.compile(&program).unwrap();
+
+    let span = &compiler.disasm()[0];
+    let found = compiler.node_at_offset(span.start).unwrap();
+    assert_eq!(found.description, span.description);
+
+    let out_of_range = compiler.get_output().len();
+    assert!(matches!(
+        compiler.node_at_offset(out_of_range),
+        Err(DisasmError::OffsetOutOfRange { .. })
+    ));
+}
+
+#[test]
+fn test_bfl_optimized_output_matches_raw_output() {
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("x".to_string(), Box::new(BFLNode::Number(7))),
+        BFLNode::Assign("y".to_string(), Box::new(BFLNode::Variable("x".to_string()))),
+        BFLNode::Assign("z".to_string(), Box::new(BFLNode::Variable("x".to_string()))),
+    ]);
+    compiler.compile(&program).unwrap();
+
+    let raw_code = compiler.get_output().to_string();
+    let optimized_code = compiler.get_optimized_output_copy();
+    assert!(optimized_code.len() < raw_code.len());
+
+    let x_addr = compiler.get_variable_address("x").unwrap();
+    let y_addr = compiler.get_variable_address("y").unwrap();
+    let z_addr = compiler.get_variable_address("z").unwrap();
+
+    let mut raw_bf = BF::new(&raw_code, Mode::BFA);
+    raw_bf.run().unwrap();
+    let mut optimized_bf = BF::new(&optimized_code, Mode::BFA);
+    optimized_bf.run().unwrap();
+
+    let max_addr = x_addr.max(y_addr).max(z_addr) + 1;
+    assert_eq!(
+        raw_bf.dump_cells(max_addr),
+        optimized_bf.dump_cells(max_addr)
+    );
+}
+
+#[test]
+fn test_bfir_recognizes_copy_loop_as_mul_add() {
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("x".to_string(), Box::new(BFLNode::Number(3))),
+        BFLNode::Assign("y".to_string(), Box::new(BFLNode::Variable("x".to_string()))),
+        // Reading `x` again keeps it live past `y = x`, so `y` gets its own
+        // cell and the copy loop is actually emitted.
+        BFLNode::Assign("z".to_string(), Box::new(BFLNode::Variable("x".to_string()))),
+    ]);
+    compiler.compile(&program).unwrap();
+
+    let ops = ewor_brainfuck::bfir::optimize(ewor_brainfuck::bfir::parse(compiler.get_output()));
+    let contains_mul_add = ops
+        .iter()
+        .any(|op| matches!(op, BfOp::MulAdd(_)));
+    assert!(contains_mul_add, "expected copy loop to collapse into MulAdd");
+}
+
+#[test]
+fn test_bfl_print_number() {
+    // Backed by a MockIoBackend so the digits written to stdout are
+    // assertable instead of going to the real terminal.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("n".to_string(), Box::new(BFLNode::Number(123))),
+        BFLNode::PrintNumber(Box::new(BFLNode::Variable("n".to_string()))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::with_backend(bf_code, Mode::BFA, Box::new(MockIoBackend::default()));
+    bf.run().unwrap();
+    let backend = bf.backend().as_any().downcast_ref::<MockIoBackend>().unwrap();
+    assert_eq!(backend.output(1), b"123");
+}
+
+#[test]
+fn test_bfl_print_number_zero() {
+    // n == 0 is special-cased since the divmod-by-10 extraction loop would
+    // otherwise produce no digits at all.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("n".to_string(), Box::new(BFLNode::Number(0))),
+        BFLNode::PrintNumber(Box::new(BFLNode::Variable("n".to_string()))),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::with_backend(bf_code, Mode::BFA, Box::new(MockIoBackend::default()));
+    bf.run().unwrap();
+    let backend = bf.backend().as_any().downcast_ref::<MockIoBackend>().unwrap();
+    assert_eq!(backend.output(1), b"0");
+}
+
+#[test]
+fn test_bfl_index_read() {
+    // buf[2] should read back the third byte ('c') of the buffer.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("buf".to_string(), Box::new(BFLNode::Bytes(b"abcdef".to_vec()))),
+        BFLNode::Assign("i".to_string(), Box::new(BFLNode::Number(2))),
+        BFLNode::Assign(
+            "val".to_string(),
+            Box::new(BFLNode::Index(
+                Box::new(BFLNode::Variable("buf".to_string())),
+                Box::new(BFLNode::Variable("i".to_string())),
+            )),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let val_addr = compiler.get_variable_address("val").unwrap();
+    assert_eq!(bf.dump_cells(val_addr + 1)[val_addr], b'c' as u32);
+}
+
+#[test]
+fn test_bfl_index_write() {
+    // Overwrite buf[2] via IndexAssign, then read every byte back out via
+    // Index rather than a single multi-byte `write` - `peek`/`poke` only
+    // ever touch one byte at a time, so per-byte reads are what this node
+    // pair is meant to guarantee.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("buf".to_string(), Box::new(BFLNode::Bytes(b"abcdef".to_vec()))),
+        BFLNode::Assign("i".to_string(), Box::new(BFLNode::Number(2))),
+        BFLNode::IndexAssign(
+            Box::new(BFLNode::Variable("buf".to_string())),
+            Box::new(BFLNode::Variable("i".to_string())),
+            Box::new(BFLNode::Number(b'X' as i32)),
+        ),
+        BFLNode::Assign(
+            "b0".to_string(),
+            Box::new(BFLNode::Index(Box::new(BFLNode::Variable("buf".to_string())), Box::new(BFLNode::Number(0)))),
+        ),
+        BFLNode::Assign(
+            "b2".to_string(),
+            Box::new(BFLNode::Index(Box::new(BFLNode::Variable("buf".to_string())), Box::new(BFLNode::Number(2)))),
+        ),
+        BFLNode::Assign(
+            "b5".to_string(),
+            Box::new(BFLNode::Index(Box::new(BFLNode::Variable("buf".to_string())), Box::new(BFLNode::Number(5)))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let b0_addr = compiler.get_variable_address("b0").unwrap();
+    let b2_addr = compiler.get_variable_address("b2").unwrap();
+    let b5_addr = compiler.get_variable_address("b5").unwrap();
+    let cells = bf.dump_cells(b0_addr.max(b2_addr).max(b5_addr) + 1);
+    assert_eq!(cells[b0_addr], b'a' as u32);
+    assert_eq!(cells[b2_addr], b'X' as u32);
+    assert_eq!(cells[b5_addr], b'f' as u32);
+}
+
+#[test]
+fn test_bfl_poll_no_events_requested() {
+    // A single all-zero `struct pollfd` (fd=0/stdin, events=0) asks poll(2)
+    // to watch nothing, so with timeout_ms=0 it returns immediately with
+    // zero ready fds and an untouched revents field - deterministic without
+    // needing a real pending connection.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("fds".to_string(), Box::new(BFLNode::Bytes(vec![0, 0, 0, 0, 0, 0, 0, 0]))),
+        BFLNode::Poll(
+            Box::new(BFLNode::Variable("fds".to_string())),
+            Box::new(BFLNode::Number(1)),
+            Box::new(BFLNode::Number(0)),
+        ),
+        BFLNode::Assign("ready".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+        BFLNode::Assign(
+            "revents".to_string(),
+            Box::new(BFLNode::Index(Box::new(BFLNode::Variable("fds".to_string())), Box::new(BFLNode::Number(6)))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let ready_addr = compiler.get_variable_address("ready").unwrap();
+    let revents_addr = compiler.get_variable_address("revents").unwrap();
+    let cells = bf.dump_cells(ready_addr.max(revents_addr) + 1);
+    assert_eq!(cells[ready_addr], 0);
+    assert_eq!(cells[revents_addr], 0);
+}
+
+#[test]
+fn test_bfl_poll_observes_pollin_on_ready_fd() {
+    use std::io::Write;
+    use std::os::unix::io::AsRawFd;
+    use std::os::unix::net::UnixStream;
+
+    // A connected socketpair where one end has data buffered is immediately
+    // POLLIN-ready on the other end - deterministic without a real network
+    // peer, and proves `Index(fds, 6)` actually reads back the `revents`
+    // byte `poll(2)` sets, which the all-zero fixture above can't.
+    let (mut writer, reader) = UnixStream::pair().unwrap();
+    writer.write_all(b"x").unwrap();
+    let reader_fd = reader.as_raw_fd() as u32;
+    let fd_bytes = reader_fd.to_le_bytes();
+
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign(
+            "fds".to_string(),
+            Box::new(BFLNode::Bytes(vec![
+                fd_bytes[0], fd_bytes[1], fd_bytes[2], fd_bytes[3], // fd
+                1, 0, // events = POLLIN
+                0, 0, // revents, initially 0
+            ])),
+        ),
+        BFLNode::Poll(
+            Box::new(BFLNode::Variable("fds".to_string())),
+            Box::new(BFLNode::Number(1)),
+            Box::new(BFLNode::Number(0)),
+        ),
+        BFLNode::Assign("ready".to_string(), Box::new(BFLNode::Variable("_syscall_result".to_string()))),
+        BFLNode::Assign(
+            "revents".to_string(),
+            Box::new(BFLNode::Index(Box::new(BFLNode::Variable("fds".to_string())), Box::new(BFLNode::Number(6)))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let ready_addr = compiler.get_variable_address("ready").unwrap();
+    let revents_addr = compiler.get_variable_address("revents").unwrap();
+    let cells = bf.dump_cells(ready_addr.max(revents_addr) + 1);
+    assert_eq!(cells[ready_addr], 1);
+    assert_eq!(cells[revents_addr] & POLLIN as u32, POLLIN as u32);
+}
+
+#[test]
+fn test_bfl_is_error_true_for_encoded_failure() {
+    // A raw syscall result encoded by `BF::encode_syscall_result` as
+    // SYSCALL_ERROR_BASE + errno should read back as an error.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign(
+            "result".to_string(),
+            Box::new(BFLNode::Number(SYSCALL_ERROR_BASE + EAGAIN)),
+        ),
+        BFLNode::Assign(
+            "is_err".to_string(),
+            Box::new(BFLNode::IsError(Box::new(BFLNode::Variable("result".to_string())))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let is_err_addr = compiler.get_variable_address("is_err").unwrap();
+    assert_eq!(bf.dump_cells(is_err_addr + 1)[is_err_addr], 1);
+}
+
+#[test]
+fn test_bfl_is_error_false_for_success_value() {
+    // An ordinary small success value (e.g. a byte count or fd) must not be
+    // mistaken for an encoded failure.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign("result".to_string(), Box::new(BFLNode::Number(42))),
+        BFLNode::Assign(
+            "is_err".to_string(),
+            Box::new(BFLNode::IsError(Box::new(BFLNode::Variable("result".to_string())))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
+    let mut bf = BF::new(bf_code, Mode::BFA);
+    bf.run().unwrap();
+    let is_err_addr = compiler.get_variable_address("is_err").unwrap();
+    assert_eq!(bf.dump_cells(is_err_addr + 1)[is_err_addr], 0);
+}
+
+#[test]
+fn test_bfl_errno_recovers_original_error_code() {
+    // `Errno` should undo the SYSCALL_ERROR_BASE offset and hand back the
+    // original errno a failed syscall encoded.
+    let mut compiler = BFLCompiler::new();
+    let program = BFLNode::Block(vec![
+        BFLNode::Assign(
+            "result".to_string(),
+            Box::new(BFLNode::Number(SYSCALL_ERROR_BASE + EAGAIN)),
+        ),
+        BFLNode::Assign(
+            "errno".to_string(),
+            Box::new(BFLNode::Errno(Box::new(BFLNode::Variable("result".to_string())))),
+        ),
+    ]);
+    compiler.compile(&program).unwrap();
+    let bf_code = compiler.get_output();
     let mut bf = BF::new(bf_code, Mode::BFA);
-    let _ = bf.run();
-    // No assert: just check that no panic occurs
-} 
\ No newline at end of file
+    bf.run().unwrap();
+    let errno_addr = compiler.get_variable_address("errno").unwrap();
+    assert_eq!(bf.dump_cells(errno_addr + 1)[errno_addr], EAGAIN as u32);
+}